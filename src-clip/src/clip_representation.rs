@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// the mime type of the plain text
+pub const TEXT_MIME: &str = "text/plain";
+/// the mime type of the image, the images are always stored as png
+pub const PNG_MIME: &str = "image/png";
+/// the mime type of the file list, each line is a file uri
+pub const URI_LIST_MIME: &str = "text/uri-list";
+/// the mime type of the html
+pub const HTML_MIME: &str = "text/html";
+/// the mime type of the rtf
+pub const RTF_MIME: &str = "text/rtf";
+
+/// one format of a clip,
+/// a single copy often puts the same content on the clipboard in several formats
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ClipRepresentation {
+    /// the mime type of the payload, like text/plain or image/png
+    pub mime: String,
+    /// the payload
+    pub data: Vec<u8>,
+}
+
+impl ClipRepresentation {
+    pub fn new(mime: &str, data: Vec<u8>) -> Self {
+        Self {
+            mime: mime.to_string(),
+            data,
+        }
+    }
+}
@@ -2,20 +2,26 @@ use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
-use crate::ClipType;
+use crate::{ClipRepresentation, ClipType};
 
 /// a single clip
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[cfg_attr(feature = "yew", derive(yew::Properties, PartialEq))]
 pub struct Clip {
     /// The text of the clip.
-    /// After the clip is created, the text should not be changed
+    /// The text can be edited, the previous texts are kept as revisions
+    ///
+    /// Only the preview of the text is loaded for the tray and the search list,
+    /// compare the len of the text with the size to know if it is the whole text
     #[serde(
         deserialize_with = "arc_string_deserialize",
         serialize_with = "arc_string_serialize"
     )]
     pub text: Arc<String>,
-    /// The type of the clip
+    /// the size of the whole text in bytes
+    #[serde(default)]
+    pub size: u64,
+    /// The type of the clip, the type of the primary representation
     pub clip_type: ClipType,
     /// in seconds
     pub timestamp: i64,
@@ -24,6 +30,12 @@ pub struct Clip {
     /// the labels of the clip
     /// each label is a string
     pub labels: Vec<String>,
+    /// all the formats of the clip, the primary one first
+    ///
+    /// they are only loaded when the clip is copied back to the clipboard,
+    /// so they are empty in the search results and the tray
+    #[serde(default)]
+    pub representations: Vec<ClipRepresentation>,
 }
 
 pub fn arc_string_deserialize<'de, D>(deserializer: D) -> Result<Arc<String>, D::Error>
@@ -2,6 +2,8 @@ use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
+use crate::{HTML_MIME, PNG_MIME, RTF_MIME, TEXT_MIME, URI_LIST_MIME};
+
 /// the type of the clip
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize, Default)]
 pub enum ClipType {
@@ -21,6 +23,19 @@ pub enum ClipType {
     Rtf,
 }
 
+impl ClipType {
+    /// the mime type of the primary representation of the clip
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Text => TEXT_MIME,
+            Self::Image => PNG_MIME,
+            Self::File => URI_LIST_MIME,
+            Self::Html => HTML_MIME,
+            Self::Rtf => RTF_MIME,
+        }
+    }
+}
+
 impl From<ClipType> for u8 {
     fn from(clip_type: ClipType) -> Self {
         match clip_type {
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// how the included labels of a `LabelFilter` are matched
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum LabelMatch {
+    /// the clip has all the included labels
+    #[default]
+    #[serde(rename = "all")]
+    All,
+    /// the clip has at least one of the included labels
+    #[serde(rename = "any")]
+    Any,
+}
+
+/// filter the clips by their labels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct LabelFilter {
+    /// the labels the clip should have, matched by `mode`
+    pub include: Vec<String>,
+    /// the clip should have none of these labels
+    pub exclude: Vec<String>,
+    pub mode: LabelMatch,
+}
+
+impl LabelFilter {
+    /// the filter matches every clip
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
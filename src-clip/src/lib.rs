@@ -1,5 +1,13 @@
+mod clip_representation;
 mod clip_struct;
 mod clip_type;
+mod label_filter;
+mod search_page;
 
+pub use clip_representation::{
+    ClipRepresentation, HTML_MIME, PNG_MIME, RTF_MIME, TEXT_MIME, URI_LIST_MIME,
+};
 pub use clip_struct::Clip;
 pub use clip_type::ClipType;
+pub use label_filter::{LabelFilter, LabelMatch};
+pub use search_page::SearchPage;
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Clip;
+
+/// a page of the search results
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SearchPage {
+    pub clips: Vec<Clip>,
+    /// pass it to the next search to get the next page, None if this is the last page
+    pub cursor: Option<String>,
+}
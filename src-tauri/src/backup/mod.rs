@@ -0,0 +1,383 @@
+/// The backup module takes the snapshots of the database in the backups dir of the app data dir
+///
+/// the snapshots are taken by the sqlite online backup api,
+/// so the database does not need to be closed
+///
+/// Backup files:
+///   - daily-[yyyy]-[mm]-[dd], taken once a day, the newest backup_keep_daily ones are kept
+///   - weekly-[yyyy]-W[ww], taken once an iso week, the newest backup_keep_weekly ones are kept
+///   - upgrade-[version]-[timestamp], taken before upgrading the database, the newest KEEP_UPGRADE_BACKUPS ones are kept
+///   - restore-[timestamp], taken before restoring a backup, the newest KEEP_RESTORE_BACKUPS ones are kept
+///
+/// the backups with an older schema version, like the upgrade ones, are upgraded after they are restored
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Datelike;
+use log::{debug, warn};
+use rusqlite::{backup::Backup, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    backward::{get_schema_version, CURRENT_SCHEMA_VERSION},
+    clip::{clip_data::ClipStateMutex, undo::UndoStack},
+    config::ConfigMutex,
+    database::{upgrade_restored_database, DatabaseStateMutex},
+    error::Error,
+    event::{event_sender, CopyClipEvent, EventSender},
+};
+
+/// how often to check whether the scheduled backups need to be taken, in seconds
+const BACKUP_INTERVAL: u64 = 60 * 60;
+
+/// the number of pages copied in each step of the online backup
+const PAGES_PER_STEP: i32 = 100;
+
+/// the prefix of the daily backups
+const DAILY_PREFIX: &str = "daily-";
+/// the prefix of the weekly backups
+const WEEKLY_PREFIX: &str = "weekly-";
+/// the prefix of the backups taken before upgrading the database
+pub const UPGRADE_PREFIX: &str = "upgrade-";
+/// the prefix of the backups taken before restoring a backup
+const RESTORE_PREFIX: &str = "restore-";
+
+/// the number of the backups taken before upgrading the database to keep
+pub const KEEP_UPGRADE_BACKUPS: u64 = 3;
+/// the number of the backups taken before restoring a backup to keep
+const KEEP_RESTORE_BACKUPS: u64 = 5;
+
+/// the suffix of the unfinished backups, they are not listed
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// the kinds of the backups, decided by the prefix of the file name
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    Daily,
+    Weekly,
+    Upgrade,
+    Restore,
+}
+
+impl BackupKind {
+    fn from_file_name(name: &str) -> Option<Self> {
+        if name.ends_with(TEMP_SUFFIX) {
+            None
+        } else if name.starts_with(DAILY_PREFIX) {
+            Some(BackupKind::Daily)
+        } else if name.starts_with(WEEKLY_PREFIX) {
+            Some(BackupKind::Weekly)
+        } else if name.starts_with(UPGRADE_PREFIX) {
+            Some(BackupKind::Upgrade)
+        } else if name.starts_with(RESTORE_PREFIX) {
+            Some(BackupKind::Restore)
+        } else {
+            None
+        }
+    }
+}
+
+/// a backup file in the backups dir
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    /// the file name, used to restore the backup
+    pub name: String,
+    pub kind: BackupKind,
+    /// the time the backup is taken, unix timestamp in seconds
+    pub timestamp: i64,
+    /// the size of the backup file in bytes
+    pub size: u64,
+}
+
+/// get the backups dir in the app data dir and create it if it does not exist
+pub fn get_and_create_backup_dir(app: &AppHandle) -> Result<PathBuf, Error> {
+    let app_data_dir = match app.path_resolver().app_data_dir() {
+        Some(app_data_dir) => app_data_dir,
+        None => return Err(Error::GetAppDataDirErr),
+    };
+
+    let backup_dir = app_data_dir.join("backups");
+    if !backup_dir.exists() {
+        if let Err(err) = std::fs::create_dir_all(backup_dir.as_path()) {
+            return Err(Error::BackupDatabaseErr(err.to_string()));
+        }
+    }
+
+    Ok(backup_dir)
+}
+
+/// copy the database to the file with the online backup api
+///
+/// the backup is written to a temporary file first,
+/// so an unfinished backup never replaces an existing one
+#[warn(unused_must_use)]
+pub fn backup_to_file(connection: &Connection, path: &Path) -> Result<(), Error> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(TEMP_SUFFIX);
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut backup_connection = match Connection::open(&temp_path) {
+        Ok(backup_connection) => backup_connection,
+        Err(err) => return Err(Error::BackupDatabaseErr(err.to_string())),
+    };
+    let backup = match Backup::new(connection, &mut backup_connection) {
+        Ok(backup) => backup,
+        Err(err) => return Err(Error::BackupDatabaseErr(err.to_string())),
+    };
+    if let Err(err) = backup.run_to_completion(PAGES_PER_STEP, Duration::ZERO, None) {
+        return Err(Error::BackupDatabaseErr(err.to_string()));
+    }
+    drop(backup);
+    if let Err((_, err)) = backup_connection.close() {
+        return Err(Error::BackupDatabaseErr(err.to_string()));
+    }
+
+    match std::fs::rename(&temp_path, path) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::BackupDatabaseErr(err.to_string())),
+    }
+}
+
+/// replace the content of the database with the backup file
+///
+/// the backup saved by a newer schema version is refused,
+/// the backup with an older schema version needs to be upgraded after it is restored
+#[warn(unused_must_use)]
+fn restore_from_file(connection: &mut Connection, path: &Path) -> Result<(), Error> {
+    let path_string = path.to_string_lossy().to_string();
+    let backup_connection =
+        match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+            Ok(backup_connection) => backup_connection,
+            Err(err) => return Err(Error::RestoreDatabaseErr(path_string, err.to_string())),
+        };
+
+    let schema_version = get_schema_version(&backup_connection)?;
+    if schema_version > CURRENT_SCHEMA_VERSION {
+        return Err(Error::RestoreDatabaseErr(
+            path_string,
+            format!(
+                "the backup has schema version {}, newer than the current schema version {}",
+                schema_version, CURRENT_SCHEMA_VERSION
+            ),
+        ));
+    }
+
+    let backup = match Backup::new(&backup_connection, connection) {
+        Ok(backup) => backup,
+        Err(err) => return Err(Error::RestoreDatabaseErr(path_string, err.to_string())),
+    };
+    match backup.run_to_completion(PAGES_PER_STEP, Duration::ZERO, None) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::RestoreDatabaseErr(path_string, err.to_string())),
+    }
+}
+
+/// list all the backups in the backups dir, the newest first
+fn list_backup_files(backup_dir: &Path) -> Result<Vec<BackupInfo>, Error> {
+    let entries = match std::fs::read_dir(backup_dir) {
+        Ok(entries) => entries,
+        Err(err) => return Err(Error::BackupDatabaseErr(err.to_string())),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => return Err(Error::BackupDatabaseErr(err.to_string())),
+        };
+        let name = entry.file_name().to_string_lossy().to_string();
+        let kind = match BackupKind::from_file_name(&name) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(err) => return Err(Error::BackupDatabaseErr(err.to_string())),
+        };
+        let timestamp = match metadata.modified() {
+            Ok(modified) => match modified.duration_since(std::time::UNIX_EPOCH) {
+                Ok(duration) => duration.as_secs() as i64,
+                Err(_) => 0,
+            },
+            Err(_) => 0,
+        };
+
+        backups.push(BackupInfo {
+            name,
+            kind,
+            timestamp,
+            size: metadata.len(),
+        });
+    }
+
+    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.name.cmp(&a.name)));
+
+    Ok(backups)
+}
+
+/// delete the oldest backups of the kind, keep the newest `keep` ones
+///
+/// the daily and weekly backups are named by the date, so the name order is the time order,
+/// the other backups are ordered by the time they are taken
+pub fn rotate_backups(backup_dir: &Path, kind: BackupKind, keep: u64) -> Result<(), Error> {
+    let mut names: Vec<String> = list_backup_files(backup_dir)?
+        .into_iter()
+        .filter(|backup| backup.kind == kind)
+        .map(|backup| backup.name)
+        .collect();
+    if matches!(kind, BackupKind::Daily | BackupKind::Weekly) {
+        names.sort_by(|a, b| b.cmp(a));
+    }
+
+    for name in names.into_iter().skip(keep as usize) {
+        debug!("delete the backup {}", name);
+        if let Err(err) = std::fs::remove_file(backup_dir.join(&name)) {
+            warn!("failed to delete the backup {}, error: {}", name, err);
+        }
+    }
+
+    Ok(())
+}
+
+/// take the daily and weekly backups if they are not taken yet,
+/// and delete the backups more than the limits in the config
+pub async fn scheduled_backup(app: &AppHandle) -> Result<(), Error> {
+    let config = app.state::<ConfigMutex>();
+    let config = config.config.lock().await;
+    let keep_daily = config.backup_keep_daily;
+    let keep_weekly = config.backup_keep_weekly;
+    drop(config);
+
+    let backup_dir = get_and_create_backup_dir(app)?;
+    let today = chrono::Local::now().date_naive();
+    let week = today.iso_week();
+
+    let mut to_take = Vec::new();
+    if keep_daily > 0 {
+        to_take.push(backup_dir.join(format!("{}{}", DAILY_PREFIX, today.format("%Y-%m-%d"))));
+    }
+    if keep_weekly > 0 {
+        to_take.push(backup_dir.join(format!(
+            "{}{}-W{:02}",
+            WEEKLY_PREFIX,
+            week.year(),
+            week.week()
+        )));
+    }
+    to_take.retain(|path| !path.exists());
+
+    if !to_take.is_empty() {
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let db_connection = db_connection.database_connection.lock().await;
+        for path in to_take.iter() {
+            debug!("back up the database to {}", path.to_string_lossy());
+            backup_to_file(&db_connection, path)?;
+        }
+        drop(db_connection);
+    }
+
+    rotate_backups(&backup_dir, BackupKind::Daily, keep_daily)?;
+    rotate_backups(&backup_dir, BackupKind::Weekly, keep_weekly)?;
+
+    Ok(())
+}
+
+/// start a background thread which takes the scheduled backups periodically
+pub fn start_backup_timer(app: &AppHandle) {
+    let app = app.app_handle();
+    let res = std::thread::Builder::new()
+        .name("backup_database".to_string())
+        .spawn(move || loop {
+            event_sender(&app, CopyClipEvent::BackupDatabaseEvent);
+            std::thread::sleep(std::time::Duration::from_secs(BACKUP_INTERVAL));
+        });
+    if let Err(err) = res {
+        warn!("failed to start the backup timer, error: {}", err);
+    }
+}
+
+/// list all the backups of the database, the newest first
+///
+/// input: {}
+///
+/// output: Vec<{
+///     name: String,
+///     kind: "daily" | "weekly" | "upgrade" | "restore",
+///     timestamp: i64,
+///     size: u64,
+/// }>
+#[tauri::command]
+pub async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, Error> {
+    let backup_dir = get_and_create_backup_dir(&app)?;
+    list_backup_files(&backup_dir)
+}
+
+/// replace the database with the backup
+///
+/// the current database is backed up to restore-[timestamp] first,
+/// so the restore can be undone
+///
+/// the backup with an older schema version is upgraded after it is restored,
+/// if the upgrade fails, the current database is put back
+///
+/// input: {
+///     name: String, the name of the backup returned by list_backups
+/// }
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    event_sender: tauri::State<'_, EventSender>,
+    name: String,
+) -> Result<(), Error> {
+    let backup_dir = get_and_create_backup_dir(&app)?;
+    // only the listed backups can be restored, so the name can not point outside the backups dir
+    let backup = list_backup_files(&backup_dir)?
+        .into_iter()
+        .find(|backup| backup.name == name);
+    if backup.is_none() {
+        return Err(Error::RestoreDatabaseErr(
+            name,
+            "the backup does not exist".to_string(),
+        ));
+    }
+
+    // lock the clip state, so no new clip is created during the restore
+    let clip_state = app.state::<ClipStateMutex>();
+    let mut clip_state = clip_state.clip_state.lock().await;
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let mut db_connection = db_connection.database_connection.lock().await;
+    let restore_path = backup_dir.join(format!(
+        "{}{}",
+        RESTORE_PREFIX,
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    ));
+    backup_to_file(&db_connection, &restore_path)?;
+    restore_from_file(&mut db_connection, &backup_dir.join(&name))?;
+    if let Err(err) = upgrade_restored_database(&db_connection, &app) {
+        restore_from_file(&mut db_connection, &restore_path)?;
+        return Err(Error::RestoreDatabaseErr(name, err.message()));
+    }
+    drop(db_connection);
+
+    // the backup restored may be one of the old ones, so they are rotated after the restore
+    rotate_backups(&backup_dir, BackupKind::Restore, KEEP_RESTORE_BACKUPS)?;
+
+    // the current clip may not exist in the backup
+    clip_state.current_clip = clip_state.get_latest_clip_id(&app).await?;
+    // the actions in the undo stack refer to the replaced database
+    clip_state.undo_stack = UndoStack::default();
+    clip_state.trigger_tray_update_event(&app).await;
+    drop(clip_state);
+
+    event_sender
+        .send(CopyClipEvent::SendNotificationEvent(format!(
+            "Restore backup {} successful.",
+            name
+        )))
+        .await;
+
+    Ok(())
+}
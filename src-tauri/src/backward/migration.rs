@@ -0,0 +1,91 @@
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+use crate::error::Error;
+
+/// one step of the upgrade of the saved data
+///
+/// each migration moves the schema version stored in `PRAGMA user_version`
+/// from `schema_version() - 1` to `schema_version()`
+pub trait Migration {
+    /// the schema version after this migration
+    fn schema_version(&self) -> u32;
+
+    /// the app version which introduced this migration,
+    /// only used to find the schema version of the databases saved before the schema version was stored
+    ///
+    /// None for the migrations added after the schema version was stored
+    fn app_version(&self) -> Option<(u64, u64, u64)>;
+
+    /// what this migration does, used in the log
+    fn description(&self) -> &'static str;
+
+    /// change the database, the connection is inside a transaction
+    fn upgrade(&self, connection: &Connection) -> Result<(), Error>;
+
+    /// change the config file, called after all the database changes are committed
+    fn upgrade_config(&self, app: &AppHandle) -> Result<(), Error>;
+}
+
+/// a migration changing the database
+pub struct DatabaseMigration {
+    pub schema_version: u32,
+    pub app_version: Option<(u64, u64, u64)>,
+    pub description: &'static str,
+    pub upgrade: fn(&Connection) -> Result<(), Error>,
+}
+
+impl Migration for DatabaseMigration {
+    fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    fn app_version(&self) -> Option<(u64, u64, u64)> {
+        self.app_version
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn upgrade(&self, connection: &Connection) -> Result<(), Error> {
+        (self.upgrade)(connection)
+    }
+
+    fn upgrade_config(&self, _app: &AppHandle) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// a migration changing the config file
+///
+/// the config file is not part of the database transaction,
+/// so it is changed only after all the database migrations are committed
+pub struct ConfigMigration {
+    pub schema_version: u32,
+    pub app_version: Option<(u64, u64, u64)>,
+    pub description: &'static str,
+    pub upgrade: fn(&AppHandle) -> Result<(), Error>,
+}
+
+impl Migration for ConfigMigration {
+    fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    fn app_version(&self) -> Option<(u64, u64, u64)> {
+        self.app_version
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn upgrade(&self, _connection: &Connection) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn upgrade_config(&self, app: &AppHandle) -> Result<(), Error> {
+        (self.upgrade)(app)
+    }
+}
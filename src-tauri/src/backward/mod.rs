@@ -1,9 +1,22 @@
+use data_encoding::BASE32;
 use log::debug;
 use rusqlite::Connection;
 use tauri::AppHandle;
 
-use crate::{backward, error::Error};
+use crate::error::Error;
 
+use self::migration::{ConfigMigration, DatabaseMigration, Migration};
+
+pub mod migration;
+mod schema_v10_deleted_at;
+mod schema_v11_revisions;
+mod schema_v12_images;
+mod schema_v13_formats;
+mod schema_v14_large_clips;
+mod schema_v15_copy_count;
+mod schema_v7_clip_labels;
+mod schema_v8_fts;
+mod schema_v9_hash;
 mod v0_2_x_to_0_3_0_database;
 mod v0_3_7_to_0_3_8_database;
 mod v0_3_8_to_0_3_9_database;
@@ -11,90 +24,268 @@ mod v0_3_x_to_0_3_3_config;
 mod v0_3_x_to_0_3_5_database;
 mod v0_3_x_to_0_3_7_database;
 
-/// parse the version number string to i32
-fn parse_version_number(version: &str) -> Result<(i32, i32, i32), Error> {
-    let version = version.split('.').collect::<Vec<&str>>();
-    if version.len() != 3 {
-        return Err(Error::GetVersionFromDatabaseErr(
-            "The version number is not correct".to_string(),
-        ));
-    }
-    // a version number is like [major].[minor].[patch]
-    let major = version[0].parse::<i32>();
-    let minor = version[1].parse::<i32>();
-    let patch = version[2].parse::<i32>();
-
-    if major.is_err() || minor.is_err() || patch.is_err() {
-        return Err(Error::GetVersionFromDatabaseErr(
-            "The version number is not correct".to_string(),
-        ));
+/// the name of the per label table used since 0.3.9, until the schema version 7,
+/// which stored the ids of the clips with that label
+///
+/// only used by the migrations, the labels are stored in the clip_labels table since the schema version 7
+fn legacy_label_table_name(label_name: &str) -> String {
+    format!(
+        "label_{}",
+        BASE32.encode(label_name.as_bytes()).replace('=', "_")
+    )
+}
+
+/// all the migrations, ordered by the schema version
+///
+/// to change the database, add a migration with the next schema version at the end,
+/// `CURRENT_SCHEMA_VERSION` must be updated at the same time
+///
+/// only the migrations released before the schema version was stored have an app version,
+/// the new migrations have none
+fn migrations() -> Vec<Box<dyn Migration>> {
+    vec![
+        Box::new(DatabaseMigration {
+            schema_version: 1,
+            app_version: Some((0, 3, 0)),
+            description: "convert the database saved before 0.3.0",
+            upgrade: v0_2_x_to_0_3_0_database::upgrade,
+        }),
+        // before 0.3.3, there is search_clip_per_page in the config file, after 0.3.3, this entry changed to search_clip_per_batch
+        Box::new(ConfigMigration {
+            schema_version: 2,
+            app_version: Some((0, 3, 3)),
+            description: "rename search_clip_per_page to search_clip_per_batch in the config file",
+            upgrade: v0_3_x_to_0_3_3_config::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 3,
+            app_version: Some((0, 3, 5)),
+            description: "rename the favorite column to favourite in the clips table",
+            upgrade: v0_3_x_to_0_3_5_database::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 4,
+            app_version: Some((0, 3, 7)),
+            description: "update the pinned clips table",
+            upgrade: v0_3_x_to_0_3_7_database::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 5,
+            app_version: Some((0, 3, 8)),
+            description: "update the pinned clips table",
+            upgrade: v0_3_7_to_0_3_8_database::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 6,
+            app_version: Some((0, 3, 9)),
+            description: "move the pinned and favourite columns to the label tables",
+            upgrade: v0_3_8_to_0_3_9_database::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 7,
+            app_version: None,
+            description: "merge the label tables into the clip_labels table",
+            upgrade: schema_v7_clip_labels::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 8,
+            app_version: None,
+            description: "add the full text search table",
+            upgrade: schema_v8_fts::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 9,
+            app_version: None,
+            description: "add the hash column to the clips table",
+            upgrade: schema_v9_hash::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 10,
+            app_version: None,
+            description: "add the deleted_at column to the clips table",
+            upgrade: schema_v10_deleted_at::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 11,
+            app_version: None,
+            description: "add the clip_revisions table",
+            upgrade: schema_v11_revisions::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 12,
+            app_version: None,
+            description: "add the clip_images table",
+            upgrade: schema_v12_images::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 13,
+            app_version: None,
+            description: "add the clip_formats table",
+            upgrade: schema_v13_formats::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 14,
+            app_version: None,
+            description: "add the preview, size and compressed_text columns to the clips table",
+            upgrade: schema_v14_large_clips::upgrade,
+        }),
+        Box::new(DatabaseMigration {
+            schema_version: 15,
+            app_version: None,
+            description: "add the copy_count column to the clips table",
+            upgrade: schema_v15_copy_count::upgrade,
+        }),
+    ]
+}
+
+/// the schema version of the database created by this version of the app,
+/// the schema version of the last migration
+pub const CURRENT_SCHEMA_VERSION: u32 = 15;
+
+/// get the schema version stored in `PRAGMA user_version`,
+/// 0 means the database was saved before the schema version was stored
+pub fn get_schema_version(connection: &Connection) -> Result<u32, Error> {
+    match connection.pragma_query_value(None, "user_version", |row| row.get(0)) {
+        Ok(version) => Ok(version),
+        Err(err) => Err(Error::GetVersionFromDatabaseErr(err.to_string())),
     }
+}
 
-    Ok((major.unwrap(), minor.unwrap(), patch.unwrap()))
+/// store the schema version in `PRAGMA user_version`
+#[warn(unused_must_use)]
+pub fn set_schema_version(connection: &Connection, version: u32) -> Result<(), Error> {
+    match connection.pragma_update(None, "user_version", version) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
 }
 
-/// deal with the backward comparability based on the save version
+/// parse the version number string like [major].[minor].[patch]
 ///
-/// return the current version
-#[warn(unused_must_use)]
-pub fn backward_comparability(
-    app: &AppHandle,
-    connection: &Connection,
-    save_version: String,
-) -> Result<String, Error> {
-    debug!("start to deal with the backward comparability");
+/// the pre-release and build parts are ignored, so 0.4.0-beta.1 is parsed as 0.4.0,
+/// the missing minor and patch numbers are 0
+fn parse_version_number(version: &str) -> Result<(u64, u64, u64), Error> {
+    let version = version.trim().trim_start_matches('v');
+    let version = match version.find(['-', '+']) {
+        Some(end) => &version[..end],
+        None => version,
+    };
 
-    // get the three version number from save_version
-    let (major, mut minor, mut patch) = parse_version_number(&save_version)?;
+    let mut numbers = [0; 3];
+    for (i, number) in version.split('.').enumerate() {
+        if i >= numbers.len() {
+            return Err(Error::GetVersionFromDatabaseErr(format!(
+                "The version number is not correct: {}",
+                version
+            )));
+        }
+        numbers[i] = match number.parse::<u64>() {
+            Ok(number) => number,
+            Err(_) => {
+                return Err(Error::GetVersionFromDatabaseErr(format!(
+                    "The version number is not correct: {}",
+                    version
+                )))
+            }
+        };
+    }
 
-    // deal with the backward comparability
+    Ok((numbers[0], numbers[1], numbers[2]))
+}
 
-    // if the major version is 0, the minor version is smaller than 3, need to upgrade the database to 0.3.0
-    if major == 0 && minor < 3 {
-        // upgrade the database to 0.3.0
-        v0_2_x_to_0_3_0_database::upgrade(connection)?;
-        minor = 3;
-        patch = 0;
-    }
+/// find the schema version of a database saved before the schema version was stored,
+/// from the last app version in the version table
+///
+/// the migrations introduced by the save version or an older version are already applied
+pub fn legacy_schema_version(save_version: &str) -> Result<u32, Error> {
+    let save_version = parse_version_number(save_version)?;
 
-    // if the major version is 0, the minor version is 3, the patch version is smaller than 3, need to upgrade the config file to 0.3.3
-    // before 0.3.3, there is search_clip_per_page in the config file, after 0.3.3, this entry changed to search_clip_per_batch
-    if major == 0 && minor == 3 && patch < 3 {
-        // upgrade the config file to 0.3.3
-        let res = v0_3_x_to_0_3_3_config::upgrade(app);
-        res?;
-        patch = 3;
+    let mut schema_version = 0;
+    for migration in migrations() {
+        if matches!(migration.app_version(), Some(app_version) if app_version <= save_version) {
+            schema_version = migration.schema_version();
+        }
     }
 
-    // when moving from 0.3.3, 0.3.4, to 0.3.5,
-    // I rename the column "favorite" to favourite in clips table, so need to do a sql ALTER command
-    if major == 0 && minor == 3 && patch < 5 {
-        v0_3_x_to_0_3_5_database::upgrade(connection)?;
-        patch = 5;
+    Ok(schema_version)
+}
+
+/// run the database part of one migration in a transaction,
+/// so a failed step is rolled back instead of leaving tables like clips_old behind
+///
+/// the schema version is updated in the same transaction
+#[warn(unused_must_use)]
+fn upgrade_in_transaction(connection: &Connection, migration: &dyn Migration) -> Result<(), Error> {
+    let transaction = match connection.unchecked_transaction() {
+        Ok(transaction) => transaction,
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    // the transaction is rolled back when dropped
+    migration.upgrade(&transaction)?;
+    set_schema_version(&transaction, migration.schema_version())?;
+
+    match transaction.commit() {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
     }
+}
 
-    // when moving fom 0.3.5, 0.3.6, to 0.3.7,
-    // we need to update the pinned clips table
-    if major == 0 && minor == 3 && patch < 7 {
-        v0_3_x_to_0_3_7_database::upgrade(connection)?;
-        patch = 7;
+/// run the database part of all the migrations newer than the schema version in order,
+/// also used to upgrade a database restored from an older backup
+///
+/// the database saved by a newer schema version is refused
+///
+/// return the migrations applied
+#[warn(unused_must_use)]
+pub fn upgrade_database(
+    connection: &Connection,
+    schema_version: u32,
+) -> Result<Vec<Box<dyn Migration>>, Error> {
+    if schema_version > CURRENT_SCHEMA_VERSION {
+        return Err(Error::DatabaseSchemaTooNewErr(
+            schema_version,
+            CURRENT_SCHEMA_VERSION,
+        ));
     }
 
-    debug!("current version: {}.{}.{}", major, minor, patch);
+    let mut applied = Vec::new();
+    for migration in migrations() {
+        if migration.schema_version() <= schema_version {
+            continue;
+        }
 
-    // when moving from 0.3.7 to 0.3.8,
-    // we need to update the pinned clips table
-    if major == 0 && minor == 3 && patch < 8 {
-        backward::v0_3_7_to_0_3_8_database::upgrade(connection)?;
-        patch = 8;
+        debug!(
+            "upgrade to schema version {}: {}",
+            migration.schema_version(),
+            migration.description()
+        );
+        upgrade_in_transaction(connection, migration.as_ref())?;
+        applied.push(migration);
     }
 
-    // when moving from 0.3.8 to 0.3.9,
-    // we need to update the clips table and pinned clips table and favourite clips table
-    if major == 0 && minor == 3 && patch < 9 {
-        v0_3_8_to_0_3_9_database::upgrade(connection)?;
-        patch = 9;
+    Ok(applied)
+}
+
+/// deal with the backward comparability based on the schema version of the database,
+/// run all the migrations newer than the schema version in order
+///
+/// the config file is changed after all the database migrations are committed,
+/// so when a database migration fails and the database is restored from the backup,
+/// the config file is still the one matching the restored database
+///
+/// the database saved by a newer schema version is refused
+#[warn(unused_must_use)]
+pub fn backward_comparability(
+    app: &AppHandle,
+    connection: &Connection,
+    schema_version: u32,
+) -> Result<(), Error> {
+    debug!("start to deal with the backward comparability");
+
+    for migration in upgrade_database(connection, schema_version)? {
+        migration.upgrade_config(app)?;
     }
 
-    Ok(format!("{}.{}.{}", major, minor, patch))
+    Ok(())
 }
@@ -0,0 +1,46 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::{database::init_clips_deleted_at_index, error::Error};
+
+/// when moving to the schema version 10,
+/// the deleted_at column is added to the clips table, used by the trash
+/// FROM:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     hash TEXT NOT NULL DEFAULT ''
+/// );
+/// ```
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     hash TEXT NOT NULL DEFAULT '',
+///     deleted_at INTEGER DEFAULT NULL
+/// );
+/// CREATE INDEX IF NOT EXISTS clips_deleted_at_index ON clips (deleted_at);
+/// ```
+///
+/// none of the existing clips is in the trash
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the deleted_at column to the clips table");
+
+    match connection.execute(
+        "ALTER TABLE clips ADD COLUMN deleted_at INTEGER DEFAULT NULL",
+        [],
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    init_clips_deleted_at_index(connection)
+}
@@ -0,0 +1,29 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::{database::init_clip_revisions_table, error::Error};
+
+/// when moving to the schema version 11,
+/// the clip_revisions table is added, used to keep the previous texts of the edited clips
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clip_revisions (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     clip_id INTEGER NOT NULL,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     FOREIGN KEY (clip_id) REFERENCES clips (id)
+///         ON UPDATE CASCADE
+///         ON DELETE CASCADE
+/// );
+/// CREATE INDEX IF NOT EXISTS clip_revisions_clip_id_index ON clip_revisions (clip_id, id);
+/// ```
+///
+/// none of the existing clips has been edited
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the clip_revisions table");
+
+    init_clip_revisions_table(connection)
+}
@@ -0,0 +1,29 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::{database::init_clip_images_table, error::Error};
+
+/// when moving to the schema version 12,
+/// the clip_images table is added, used to store the png of the image clips
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clip_images (
+///     clip_id INTEGER PRIMARY KEY,
+///     data BLOB NOT NULL,
+///     width INTEGER NOT NULL,
+///     height INTEGER NOT NULL,
+///     size INTEGER NOT NULL,
+///     FOREIGN KEY (clip_id) REFERENCES clips (id)
+///         ON UPDATE CASCADE
+///         ON DELETE CASCADE
+/// );
+/// ```
+///
+/// none of the existing clips is an image
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the clip_images table");
+
+    init_clip_images_table(connection)
+}
@@ -0,0 +1,28 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::{database::init_clip_formats_table, error::Error};
+
+/// when moving to the schema version 13,
+/// the clip_formats table is added, used to store the html and rtf payloads of the clips
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clip_formats (
+///     clip_id INTEGER NOT NULL,
+///     format TEXT NOT NULL,
+///     data BLOB NOT NULL,
+///     PRIMARY KEY (clip_id, format),
+///     FOREIGN KEY (clip_id) REFERENCES clips (id)
+///         ON UPDATE CASCADE
+///         ON DELETE CASCADE
+/// );
+/// ```
+///
+/// all the existing clips are plain text
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the clip_formats table");
+
+    init_clip_formats_table(connection)
+}
@@ -0,0 +1,68 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::{
+    clip::text::PREVIEW_MAX_CHARS,
+    database::{drop_clips_fts_table, init_clips_fts_table, rebuild_clips_fts_table},
+    error::Error,
+};
+
+/// when moving to the schema version 14,
+/// the preview, size and compressed_text columns are added to the clips table,
+/// used by the large clips
+/// FROM:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     hash TEXT NOT NULL DEFAULT '',
+///     deleted_at INTEGER DEFAULT NULL
+/// );
+/// ```
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     hash TEXT NOT NULL DEFAULT '',
+///     deleted_at INTEGER DEFAULT NULL,
+///     preview TEXT NOT NULL DEFAULT '',
+///     size INTEGER NOT NULL DEFAULT 0,
+///     compressed_text BLOB DEFAULT NULL
+/// );
+/// ```
+///
+/// the preview and size of the existing clips are filled, none of them is compressed
+///
+/// the clips_fts table is replaced by a contentless table, its triggers index the text column,
+/// the index is rebuilt with the full text of the compressed clips
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the large clip columns to the clips table");
+
+    match connection.execute_batch(
+        "ALTER TABLE clips ADD COLUMN preview TEXT NOT NULL DEFAULT '';
+        ALTER TABLE clips ADD COLUMN size INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE clips ADD COLUMN compressed_text BLOB DEFAULT NULL;",
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    match connection.execute(
+        "UPDATE clips SET preview = substr(text, 1, ?), size = length(CAST(text AS BLOB))",
+        [PREVIEW_MAX_CHARS as i64],
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    drop_clips_fts_table(connection)?;
+    init_clips_fts_table(connection)?;
+    rebuild_clips_fts_table(connection)
+}
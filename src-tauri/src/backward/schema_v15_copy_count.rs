@@ -0,0 +1,52 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::error::Error;
+
+/// when moving to the schema version 15,
+/// the copy_count column is added to the clips table,
+/// used to count the copies of a text after its duplicates are deleted
+/// FROM:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     hash TEXT NOT NULL DEFAULT '',
+///     deleted_at INTEGER DEFAULT NULL,
+///     preview TEXT NOT NULL DEFAULT '',
+///     size INTEGER NOT NULL DEFAULT 0,
+///     compressed_text BLOB DEFAULT NULL
+/// );
+/// ```
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     hash TEXT NOT NULL DEFAULT '',
+///     deleted_at INTEGER DEFAULT NULL,
+///     preview TEXT NOT NULL DEFAULT '',
+///     size INTEGER NOT NULL DEFAULT 0,
+///     compressed_text BLOB DEFAULT NULL,
+///     copy_count INTEGER NOT NULL DEFAULT 1
+/// );
+/// ```
+///
+/// every existing clip counts as one copy, the duplicates deleted before are not known
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the copy_count column to the clips table");
+
+    match connection.execute(
+        "ALTER TABLE clips ADD COLUMN copy_count INTEGER NOT NULL DEFAULT 1",
+        [],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
@@ -0,0 +1,78 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::{
+    database::{get_all_labels, init_clip_labels_table},
+    error::Error,
+};
+
+use super::legacy_label_table_name;
+
+/// when moving to the schema version 7,
+/// the labels of the clips are moved into one table
+/// FROM:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS label_base32(label_name) (
+///     id INTEGER PRIMARY KEY,
+///     FOREIGN KEY (id) REFERENCES clips (id)
+/// );
+/// ```
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clip_labels (
+///     clip_id INTEGER NOT NULL,
+///     label TEXT NOT NULL,
+///     PRIMARY KEY (clip_id, label),
+///     FOREIGN KEY (clip_id) REFERENCES clips (id),
+///     FOREIGN KEY (label) REFERENCES labels (name)
+/// );
+/// ```
+///
+/// the rows of deleted clips left in the label tables are not copied
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("merge the label tables into the clip_labels table");
+
+    init_clip_labels_table(connection)?;
+
+    let labels = get_all_labels(connection)?;
+    for label in labels {
+        let table_name = legacy_label_table_name(&label);
+
+        // the label table may not exist, if the label was created but never used
+        let exists = match connection.query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [&table_name],
+            |_| Ok(()),
+        ) {
+            Ok(_) => true,
+            Err(rusqlite::Error::QueryReturnedNoRows) => false,
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        if !exists {
+            continue;
+        }
+
+        // copy the clips of the label into the new table
+        match connection.execute(
+            &format!(
+                "INSERT OR IGNORE INTO clip_labels (clip_id, label)
+                SELECT id, ? FROM {} WHERE id IN (SELECT id FROM clips)",
+                table_name
+            ),
+            [&label],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+
+        // drop the old label table
+        match connection.execute(&format!("DROP TABLE {}", table_name), []) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::error::Error;
+
+/// when moving to the schema version 8,
+/// the clips_fts table is added for the full text search
+///
+/// ```sql
+/// CREATE VIRTUAL TABLE IF NOT EXISTS clips_fts USING fts5(
+///     text,
+///     content = 'clips',
+///     content_rowid = 'id'
+/// );
+/// ```
+///
+/// the table is created with its triggers, and then the existing clips are indexed
+///
+/// the table and the triggers are created with the definition of this version,
+/// they are replaced by the contentless table when the large clips are added
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the full text search table");
+
+    match connection.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS clips_fts USING fts5(
+            text,
+            content = 'clips',
+            content_rowid = 'id'
+        );
+        CREATE TRIGGER IF NOT EXISTS clips_fts_insert AFTER INSERT ON clips BEGIN
+            INSERT INTO clips_fts (rowid, text) VALUES (new.id, new.text);
+        END;
+        CREATE TRIGGER IF NOT EXISTS clips_fts_delete AFTER DELETE ON clips BEGIN
+            INSERT INTO clips_fts (clips_fts, rowid, text) VALUES ('delete', old.id, old.text);
+        END;
+        CREATE TRIGGER IF NOT EXISTS clips_fts_update AFTER UPDATE OF text ON clips BEGIN
+            INSERT INTO clips_fts (clips_fts, rowid, text) VALUES ('delete', old.id, old.text);
+            INSERT INTO clips_fts (rowid, text) VALUES (new.id, new.text);
+        END;
+        INSERT INTO clips_fts (clips_fts) VALUES ('rebuild');",
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
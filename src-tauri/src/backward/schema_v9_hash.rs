@@ -0,0 +1,50 @@
+use log::debug;
+use rusqlite::Connection;
+
+use crate::{database::init_clips_hash_index, error::Error};
+
+/// when moving to the schema version 9,
+/// the hash column is added to the clips table
+/// FROM:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL
+/// );
+/// ```
+///
+/// TO:
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS clips (
+///     id INTEGER PRIMARY KEY AUTOINCREMENT,
+///     type INTEGER NOT NULL DEFAULT 0,
+///     text TEXT NOT NULL,
+///     timestamp INTEGER NOT NULL,
+///     hash TEXT NOT NULL DEFAULT ''
+/// );
+/// CREATE INDEX IF NOT EXISTS clips_hash_index ON clips (hash);
+/// ```
+///
+/// the hash of the existing clips is computed with the clip_hash sql function
+#[warn(unused_must_use)]
+pub fn upgrade(connection: &Connection) -> Result<(), Error> {
+    debug!("add the hash column to the clips table");
+
+    match connection.execute(
+        "ALTER TABLE clips ADD COLUMN hash TEXT NOT NULL DEFAULT ''",
+        [],
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    // backfill the hash of the existing clips
+    match connection.execute("UPDATE clips SET hash = clip_hash(text)", []) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    init_clips_hash_index(connection)
+}
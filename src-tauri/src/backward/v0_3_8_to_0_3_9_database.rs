@@ -1,7 +1,9 @@
 use log::debug;
 use rusqlite::Connection;
 
-use crate::{database::label_name_to_table_name, error::Error};
+use crate::error::Error;
+
+use super::legacy_label_table_name;
 
 /// when moving from 0.3.8 to 0.3.9,
 /// we need to update the pinned clips table
@@ -102,7 +104,7 @@ pub fn upgrade(connection: &Connection) -> Result<(), Error> {
                         ON UPDATE CASCADE
                         ON DELETE CASCADE
                 )",
-                label_name_to_table_name(&label)
+                legacy_label_table_name(&label)
             ),
             [],
         ) {
@@ -116,7 +118,7 @@ pub fn upgrade(connection: &Connection) -> Result<(), Error> {
         &format!(
             "INSERT INTO {} (id)
             SELECT id FROM clips_old WHERE pinned = 1",
-            label_name_to_table_name("pinned")
+            legacy_label_table_name("pinned")
         ),
         [],
     ) {
@@ -129,7 +131,7 @@ pub fn upgrade(connection: &Connection) -> Result<(), Error> {
         &format!(
             "INSERT INTO {} (id)
             SELECT id FROM clips_old WHERE favourite = 1",
-            label_name_to_table_name("favourite")
+            legacy_label_table_name("favourite")
         ),
         [],
     ) {
@@ -1,19 +1,29 @@
 use crate::{
     clip::get_system_timestamp,
-    config::ConfigMutex,
-    database::{label_name_to_table_name, DatabaseStateMutex},
+    config::{ConfigMutex, LargeClipPolicy},
+    database::{
+        clip_hash, create_label, data_hash, index_compressed_text, DatabaseStateMutex,
+        DEFAULT_LABELS,
+    },
     error::Error,
     event::{CopyClipEvent, EventSender},
 };
 use std::sync::Arc;
 
-use clip::{Clip, ClipType};
+use clip::{Clip, ClipRepresentation, ClipType, HTML_MIME, PNG_MIME, RTF_MIME};
 use log::debug;
 use once_cell::sync::Lazy;
 use tauri::{async_runtime::Mutex, AppHandle, Manager};
 use unicode_segmentation::UnicodeSegmentation;
 
-use super::copy_clip_to_clipboard_in;
+use super::{
+    copy_clip_to_clipboard_in,
+    file::{file_clip_text, normalize_file_paths},
+    format::insert_clip_formats,
+    image::{image_clip_text, insert_clip_image, png_dimensions, ClipImage},
+    text::{truncate_text, StoredText},
+    undo::{get_duplicate_clips, get_label_clip_ids, UndoAction, UndoStack},
+};
 
 /// The clip data to be shared between threads
 #[derive(Debug, Default, Clone)]
@@ -22,6 +32,8 @@ pub struct ClipState {
     pub current_clip: Option<u64>,
     /// the current page
     pub current_page: u64,
+    /// the destructive actions that can be undone, the latest last
+    pub undo_stack: UndoStack,
 }
 
 /// The clip data to be shared between threads
@@ -90,11 +102,8 @@ impl ClipState {
         let db_connection = db_connection.database_connection.lock().await;
 
         match db_connection.query_row(
-            &format!(
-                "SELECT id FROM {} WHERE id = ?",
-                label_name_to_table_name(label)
-            ),
-            [id.to_string()],
+            "SELECT clip_id FROM clip_labels WHERE clip_id = ? AND label = ?",
+            rusqlite::params![id, label],
             |_| Ok(()),
         ) {
             Ok(_) => Ok(true),
@@ -110,9 +119,11 @@ impl ClipState {
         let db_connection = app.state::<DatabaseStateMutex>();
         let db_connection = db_connection.database_connection.lock().await;
 
-        match db_connection.query_row("SELECT id FROM clips ORDER BY id DESC LIMIT 1", [], |row| {
-            row.get(0)
-        }) {
+        match db_connection.query_row(
+            "SELECT id FROM clips WHERE deleted_at IS NULL ORDER BY id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        ) {
             Ok(res) => Ok(Some(res)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(err) => Err(Error::GetClipDataFromDatabaseErr(0, err.to_string())),
@@ -127,8 +138,9 @@ impl ClipState {
         let db_connection = db_connection.database_connection.lock().await;
 
         let res = match db_connection.query_row(
-            &format!("SELECT COUNT(1) FROM {}", label_name_to_table_name(label)),
-            [],
+            "SELECT COUNT(1) FROM clip_labels
+            WHERE label = ? AND clip_id IN (SELECT id FROM clips WHERE deleted_at IS NULL)",
+            [label],
             |row| row.get(0),
         ) {
             Ok(res) => res,
@@ -156,11 +168,10 @@ impl ClipState {
         let db_connection = db_connection.database_connection.lock().await;
 
         let res = match db_connection.query_row(
-            &format!(
-                "SELECT id FROM {} ORDER BY id ASC LIMIT 1 OFFSET ?",
-                label_name_to_table_name(label)
-            ),
-            [pos.to_string()],
+            "SELECT clip_id FROM clip_labels
+            WHERE label = ? AND clip_id IN (SELECT id FROM clips WHERE deleted_at IS NULL)
+            ORDER BY clip_id ASC LIMIT 1 OFFSET ?",
+            rusqlite::params![label, pos],
             |row| row.get(0),
         ) {
             Ok(res) => res,
@@ -176,6 +187,7 @@ impl ClipState {
     /// add or delete a label to a clip
     ///   - if target is true, add the label to the clip
     ///   - if target is false, delete the label from the clip
+    ///   - record the change in the undo stack if the label of the clip changed
     ///   - trigger a tray update event
     pub async fn change_clip_label(
         &mut self,
@@ -189,16 +201,13 @@ impl ClipState {
 
         // change the clip in the database
         // wait for any error
-        if target {
+        let changed = if target {
             // add the label to the clip
             match db_connection.execute(
-                &format!(
-                    "INSERT OR IGNORE INTO {} (id) VALUES (?)",
-                    label_name_to_table_name(label)
-                ),
-                [id.to_string()],
+                "INSERT OR IGNORE INTO clip_labels (clip_id, label) VALUES (?, ?)",
+                rusqlite::params![id, label],
             ) {
-                Ok(_) => (),
+                Ok(res) => res,
                 Err(err) => {
                     return Err(Error::UpdateClipsInDatabaseErr(
                         format!(
@@ -212,13 +221,10 @@ impl ClipState {
         } else {
             // remove the label from the clip
             match db_connection.execute(
-                &format!(
-                    "DELETE FROM {} WHERE id = ?",
-                    label_name_to_table_name(label),
-                ),
-                [id.to_string()],
+                "DELETE FROM clip_labels WHERE clip_id = ? AND label = ?",
+                rusqlite::params![id, label],
             ) {
-                Ok(_) => (),
+                Ok(res) => res,
                 Err(err) => {
                     return Err(Error::UpdateClipsInDatabaseErr(
                         format!(
@@ -230,6 +236,15 @@ impl ClipState {
                 }
             }
         };
+        drop(db_connection);
+
+        if changed > 0 {
+            self.undo_stack.push(UndoAction::ChangeClipLabel {
+                id,
+                label: label.to_string(),
+                target,
+            });
+        }
 
         // trigger a tray update event
         self.trigger_tray_update_event(app).await;
@@ -261,12 +276,163 @@ impl ClipState {
         self.change_clip_label(app, id, "pinned", target).await
     }
 
-    /// Delete a clip from the database and the cache,
-    /// this method will not delete any pinned clip.
+    /// Test if a label exists in the labels table
+    pub async fn label_exists(&self, app: &AppHandle, label: &str) -> Result<bool, Error> {
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let db_connection = db_connection.database_connection.lock().await;
+
+        match db_connection.query_row(
+            "SELECT name FROM labels WHERE name = ?",
+            [label],
+            |_| Ok(()),
+        ) {
+            Ok(_) => Ok(true),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+            Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+        }
+    }
+
+    /// Create a new label
+    ///   - the label name is trimmed, and can not be empty
+    ///   - return an error if the label already exists
+    pub async fn create_label(&mut self, app: &AppHandle, label: &str) -> Result<(), Error> {
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(Error::LabelErr(
+                "the label name can not be empty".to_string(),
+            ));
+        }
+        if self.label_exists(app, label).await? {
+            return Err(Error::LabelErr(format!("label {} already exists", label)));
+        }
+
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let db_connection = db_connection.database_connection.lock().await;
+        create_label(&db_connection, label)
+    }
+
+    /// Rename a label, the clips with the old label will have the new label
+    ///   - the default labels can not be renamed
+    ///   - return an error if the new label already exists
+    ///   - trigger a tray update event
+    pub async fn rename_label(
+        &mut self,
+        app: &AppHandle,
+        label: &str,
+        new_label: &str,
+    ) -> Result<(), Error> {
+        let new_label = new_label.trim();
+        if DEFAULT_LABELS.contains(&label) {
+            return Err(Error::LabelErr(format!(
+                "the default label {} can not be renamed",
+                label
+            )));
+        }
+        if new_label.is_empty() {
+            return Err(Error::LabelErr(
+                "the label name can not be empty".to_string(),
+            ));
+        }
+        if !self.label_exists(app, label).await? {
+            return Err(Error::LabelErr(format!("label {} does not exist", label)));
+        }
+        if self.label_exists(app, new_label).await? {
+            return Err(Error::LabelErr(format!(
+                "label {} already exists",
+                new_label
+            )));
+        }
+
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let mut db_connection = db_connection.database_connection.lock().await;
+        let transaction = match db_connection.transaction() {
+            Ok(transaction) => transaction,
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+
+        // the new label is inserted before the clips are moved,
+        // so the clip_labels rows always reference an existing label
+        match transaction.execute("INSERT INTO labels (name) VALUES (?)", [new_label]) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        match transaction.execute(
+            "UPDATE clip_labels SET label = ? WHERE label = ?",
+            [new_label, label],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        match transaction.execute("DELETE FROM labels WHERE name = ?", [label]) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+
+        if let Err(err) = transaction.commit() {
+            return Err(Error::DatabaseWriteErr(err.to_string()));
+        }
+        drop(db_connection);
+
+        self.trigger_tray_update_event(app).await;
+
+        Ok(())
+    }
+
+    /// Delete a label, and remove the label from all the clips
+    ///   - the default labels can not be deleted
+    ///   - the clips themselves are not deleted
+    ///   - record the deletion in the undo stack
+    ///   - trigger a tray update event
+    pub async fn delete_label(&mut self, app: &AppHandle, label: &str) -> Result<(), Error> {
+        if DEFAULT_LABELS.contains(&label) {
+            return Err(Error::LabelErr(format!(
+                "the default label {} can not be deleted",
+                label
+            )));
+        }
+        if !self.label_exists(app, label).await? {
+            return Err(Error::LabelErr(format!("label {} does not exist", label)));
+        }
+
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let mut db_connection = db_connection.database_connection.lock().await;
+        let clip_ids = get_label_clip_ids(&db_connection, label)?;
+        let transaction = match db_connection.transaction() {
+            Ok(transaction) => transaction,
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+
+        match transaction.execute("DELETE FROM clip_labels WHERE label = ?", [label]) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        match transaction.execute("DELETE FROM labels WHERE name = ?", [label]) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+
+        if let Err(err) = transaction.commit() {
+            return Err(Error::DatabaseWriteErr(err.to_string()));
+        }
+        drop(db_connection);
+
+        self.undo_stack.push(UndoAction::DeleteLabel {
+            label: label.to_string(),
+            clip_ids,
+        });
+
+        self.trigger_tray_update_event(app).await;
+
+        Ok(())
+    }
+
+    /// Move a clip to the trash, the clip can be restored until the trash is emptied.
     ///
     /// None represent the latest clip.
     ///
-    /// Will not trigger a tray update event.
+    /// The move is recorded in the undo stack.
+    ///
+    /// Will trigger a tray update event.
     pub async fn delete_clip(&mut self, app: &AppHandle, id: Option<u64>) -> Result<(), Error> {
         // if id is none, change it to the latest clip
         let id = match id {
@@ -277,20 +443,54 @@ impl ClipState {
             },
         };
 
-        // delete in database
+        // move to the trash in database
         // wait for any error
         let db_connection = app.state::<DatabaseStateMutex>();
         let db_connection = db_connection.database_connection.lock().await;
-        match db_connection.execute("DELETE FROM clips WHERE id = ?", [id.to_string()]) {
-            Ok(_) => (),
+        let trashed = match db_connection.execute(
+            "UPDATE clips SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+            [get_system_timestamp(), id as i64],
+        ) {
+            Ok(res) => res,
             Err(err) => return Err(Error::DeleteClipFromDatabaseErr(id, err.to_string())),
         };
+        drop(db_connection);
+
+        if trashed > 0 {
+            self.undo_stack.push(UndoAction::TrashClip(id));
+        }
+
+        // the current clip is in the trash now
+        if self.current_clip == Some(id) {
+            self.current_clip = self.get_latest_clip_id(app).await?;
+        }
 
         self.trigger_tray_update_event(app).await;
 
         Ok(())
     }
 
+    /// Undo the latest action in the undo stack
+    ///   - return the text shown to the user, or None if there is nothing to undo
+    ///   - trigger a tray update event
+    pub async fn undo_last_action(&mut self, app: &AppHandle) -> Result<Option<String>, Error> {
+        let action = match self.undo_stack.pop() {
+            Some(action) => action,
+            None => return Ok(None),
+        };
+
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let db_connection = db_connection.database_connection.lock().await;
+        let res = action.undo(&db_connection);
+        drop(db_connection);
+
+        // the tray is rebuilt even if the undo failed, the undo item may be disabled now
+        self.trigger_tray_update_event(app).await;
+        res?;
+
+        Ok(Some(action.description()))
+    }
+
     /// Change the current page to the first page.
     ///
     /// Will try lock `clips`.
@@ -308,7 +508,7 @@ impl ClipState {
 
         // get all the labels from the labels table
         let mut labels: Vec<String> = Vec::new();
-        let mut statement = match db_connection.prepare("SELECT name FROM labels") {
+        let mut statement = match db_connection.prepare("SELECT name FROM labels ORDER BY name") {
             Ok(prepared_statement) => prepared_statement,
             Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
         };
@@ -334,34 +534,35 @@ impl ClipState {
         app: &AppHandle,
         id: u64,
     ) -> Result<Option<Vec<String>>, Error> {
-        let all_labels = self.get_all_labels(app).await?;
-
         let db_connection = app.state::<DatabaseStateMutex>();
         let db_connection = db_connection.database_connection.lock().await;
 
+        let mut statement = match db_connection
+            .prepare("SELECT label FROM clip_labels WHERE clip_id = ? ORDER BY label ASC")
+        {
+            Ok(prepared_statement) => prepared_statement,
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        let statement = match statement.query_map([id], |row| row.get(0)) {
+            Ok(statement) => statement,
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
         let mut labels: Vec<String> = Vec::new();
-        for label in all_labels {
-            let mut statement = match db_connection.prepare(&format!(
-                "SELECT * FROM {} WHERE id = ?",
-                label_name_to_table_name(&label)
-            )) {
-                Ok(prepared_statement) => prepared_statement,
-                Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
-            };
-            match statement.query_row([id.to_string()], |_| Ok(())) {
-                Ok(_) => labels.push(label),
-                Err(rusqlite::Error::QueryReturnedNoRows) => (),
+        for label in statement {
+            match label {
+                Ok(label) => labels.push(label),
                 Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
-            };
+            }
         }
 
         Ok(Some(labels))
     }
 
-    /// Get a clip by id from database
+    /// Get a clip by id from database, with the preview as the text
     ///
     /// Get the latest clip if the id is none.
     /// None if the clip is not found.
+    /// The full text is loaded only when the clip is copied, see `text::get_clip_text`.
     #[warn(unused_must_use)]
     pub async fn get_clip(&self, app: &AppHandle, id: Option<u64>) -> Result<Option<Clip>, Error> {
         // if id is none, change it to the latest clip
@@ -381,16 +582,19 @@ impl ClipState {
             let clip_type: u8 = row.get(1)?;
             let text: String = row.get(2)?;
             let timestamp: i64 = row.get(3)?;
+            let size: u64 = row.get(4)?;
             Ok(Clip {
                 id,
                 text: Arc::new(text),
+                size,
                 timestamp,
                 clip_type: ClipType::from(clip_type),
                 labels: Vec::new(),
+                representations: Vec::new(),
             })
         }
         let mut res = match db_connection.query_row(
-            "SELECT id, type, text, timestamp FROM clips WHERE id = ?",
+            "SELECT id, type, preview, timestamp, size FROM clips WHERE id = ? AND deleted_at IS NULL",
             [id],
             get_clip_from_row,
         ) {
@@ -445,7 +649,7 @@ impl ClipState {
         let db_connection = db_connection.database_connection.lock().await;
 
         let res: u64 = match db_connection.query_row(
-            "SELECT COUNT(1) FROM clips WHERE id <= ?",
+            "SELECT COUNT(1) FROM clips WHERE id <= ? AND deleted_at IS NULL",
             [id.to_string()],
             |row| row.get(0),
         ) {
@@ -480,25 +684,138 @@ impl ClipState {
         Ok(max_page)
     }
 
-    /// Create a new clip in the database and return the id of the new clip
-    pub async fn new_clip(&mut self, app: &AppHandle, text: Arc<String>) -> Result<u64, Error> {
+    /// Create a new text, html or rtf clip in the database and return the id of the new clip
+    ///
+    /// The text of the clip is the plain text, used by the search and the tray,
+    /// the clip is a html clip if the html is given, a rtf clip if the rtf is given,
+    /// otherwise a text clip.
+    /// The other representations are stored with the clip.
+    /// The hash is the hash of the plain text.
+    ///
+    /// The duplicates removed by auto delete duplicate clip are recorded in the undo stack.
+    pub async fn new_clip(
+        &mut self,
+        app: &AppHandle,
+        text: Arc<String>,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<u64, Error> {
         debug!("Create a new clip");
-        let id: u64 = if let Some(id) = self.get_latest_clip_id(app).await? {
-            id + 1
+        let has = |mime: &str| {
+            representations
+                .iter()
+                .any(|representation| representation.mime == mime)
+        };
+        let clip_type = if has(HTML_MIME) {
+            ClipType::Html
+        } else if has(RTF_MIME) {
+            ClipType::Rtf
         } else {
-            1
+            ClipType::Text
+        };
+        let hash = clip_hash(&text);
+        self.insert_new_clip(app, clip_type, text, hash, None, representations)
+            .await
+    }
+
+    /// Create a new file clip in the database and return the id of the new clip
+    ///
+    /// The text of the clip is the file paths, each line is a file path.
+    /// The other representations are stored with the clip.
+    pub async fn new_file_clip(
+        &mut self,
+        app: &AppHandle,
+        paths: Vec<String>,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<u64, Error> {
+        debug!("Create a new file clip");
+        let text = Arc::new(file_clip_text(&paths));
+        let hash = clip_hash(&text);
+        self.insert_new_clip(app, ClipType::File, text, hash, None, representations)
+            .await
+    }
+
+    /// Create a new image clip in the database from a png and return the id of the new clip
+    ///
+    /// The text of the clip is a short description of the image,
+    /// and the hash is the hash of the png.
+    /// The other representations are stored with the clip.
+    pub async fn new_image_clip(
+        &mut self,
+        app: &AppHandle,
+        data: Vec<u8>,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<u64, Error> {
+        debug!("Create a new image clip");
+        let (width, height) = match png_dimensions(&data) {
+            Some(dimensions) => dimensions,
+            None => {
+                return Err(Error::ReadFromSystemClipboardErr(
+                    "the image in the clipboard is not a png".to_string(),
+                ))
+            }
+        };
+        let hash = data_hash(&data);
+        let text = Arc::new(image_clip_text(width, height, data.len()));
+        let image = ClipImage {
+            data,
+            width,
+            height,
         };
+        self.insert_new_clip(
+            app,
+            ClipType::Image,
+            text,
+            hash,
+            Some(image),
+            representations,
+        )
+        .await
+    }
 
+    /// Insert a new clip, and its image and other representations if any,
+    /// then apply the retention policy and the auto delete duplicate clip
+    async fn insert_new_clip(
+        &mut self,
+        app: &AppHandle,
+        clip_type: ClipType,
+        text: Arc<String>,
+        hash: String,
+        image: Option<ClipImage>,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<u64, Error> {
         let timestamp = get_system_timestamp();
+        let clip_type: u8 = clip_type.into();
+
+        // the large texts are compressed, only the preview is kept in the text column
+        let config = app.state::<ConfigMutex>();
+        let config = config.config.lock().await;
+        let compress_threshold = config.compress_threshold;
+        let auto_delete_duplicate_clip = config.auto_delete_duplicate_clip;
+        drop(config);
+        let stored_text = StoredText::new(&text, compress_threshold)?;
 
         let db_connection = app.state::<DatabaseStateMutex>();
         let db_connection = db_connection.database_connection.lock().await;
 
-        let id: u64 = match db_connection.query_row(
-            "INSERT INTO clips (id, text, timestamp, type)
-            VALUES (?, ?, ?, 0)
+        // the clip and its image and representations are inserted together,
+        // with the removal of its duplicates
+        let transaction = match db_connection.unchecked_transaction() {
+            Ok(transaction) => transaction,
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        let id: u64 = match transaction.query_row(
+            "INSERT INTO clips (text, timestamp, type, hash, preview, size, compressed_text)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             RETURNING id",
-            [id.to_string(), text.to_string(), timestamp.to_string()],
+            rusqlite::params![
+                stored_text.text,
+                timestamp,
+                clip_type,
+                hash,
+                stored_text.preview,
+                stored_text.size,
+                stored_text.compressed_text
+            ],
             |row| row.get(0),
         ) {
             Ok(id) => id,
@@ -509,30 +826,74 @@ impl ClipState {
                 ));
             }
         };
+        if stored_text.compressed_text.is_some() {
+            index_compressed_text(&transaction, id, &text)?;
+        }
+        if let Some(image) = image {
+            insert_clip_image(&transaction, id, &image)?;
+        }
+        insert_clip_formats(&transaction, id, &representations)?;
+
+        // keep the duplicates in the undo stack, so they can be inserted back,
+        // the clips in the trash are not duplicates, they stay in the trash
+        let duplicates = if auto_delete_duplicate_clip {
+            debug!("Start auto delete duplicate clip");
+            get_duplicate_clips(&transaction, &hash, &text, id)?
+        } else {
+            debug!("Auto delete duplicate clip is disabled");
+            Vec::new()
+        };
+        for duplicate in &duplicates {
+            match transaction.execute("DELETE FROM clips WHERE id = ?", [duplicate.id]) {
+                Ok(_) => (),
+                Err(err) => {
+                    return Err(Error::DeleteClipFromDatabaseErr(
+                        duplicate.id,
+                        err.to_string(),
+                    ));
+                }
+            };
+        }
+        // the new clip keeps counting the copies of the deleted duplicates
+        let copies: u64 = duplicates.iter().map(|clip| clip.copy_count).sum();
+        if copies > 0 {
+            match transaction.execute(
+                "UPDATE clips SET copy_count = copy_count + ? WHERE id = ?",
+                [copies, id],
+            ) {
+                Ok(_) => (),
+                Err(err) => {
+                    return Err(Error::UpdateClipsInDatabaseErr(
+                        id.to_string(),
+                        err.to_string(),
+                    ));
+                }
+            };
+        }
+
+        if let Err(err) = transaction.commit() {
+            return Err(Error::InsertClipIntoDatabaseErr(
+                (*text).clone(),
+                err.to_string(),
+            ));
+        }
+
+        drop(db_connection);
 
         // change the current clip to the last one
         self.current_clip = Some(id);
 
-        // see if we need to auto delete duplicate clip
-        let config = app.state::<ConfigMutex>();
-        let config = config.config.lock().await;
-        if !config.auto_delete_duplicate_clip {
-            debug!("Auto delete duplicate clip is disabled");
-            self.trigger_tray_update_event(app).await;
-            return Ok(id);
+        if !duplicates.is_empty() {
+            self.undo_stack.push(UndoAction::RemoveDuplicates {
+                id,
+                clips: duplicates,
+            });
         }
-        drop(config);
 
-        debug!("Start auto delete duplicate clip");
-        match db_connection.execute(
-            "DELETE FROM clips WHERE text = ? AND id != ?",
-            [text.to_string(), id.to_string()],
-        ) {
-            Ok(_) => (),
-            Err(err) => {
-                return Err(Error::DeleteClipFromDatabaseErr(id, err.to_string()));
-            }
-        };
+        // apply the retention policy after the new clip is inserted,
+        // the database is unlocked first, the event handler locks it
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::PruneClipsEvent).await;
 
         self.trigger_tray_update_event(app).await;
         Ok(id)
@@ -583,7 +944,7 @@ impl ClipState {
         self.current_clip = id;
 
         // change the clipboard
-        copy_clip_to_clipboard_in(&c, app)?;
+        copy_clip_to_clipboard_in(&c, app).await?;
 
         Ok(())
     }
@@ -612,57 +973,109 @@ impl ClipState {
 
     /// Handle the clip board change event
     ///
-    /// If the clipboard text is different from the most recent clip text,
-    /// and is different from the current clip text,
+    /// If the clipboard content is different from the most recent clip,
+    /// and is different from the current clip,
     /// and is not empty,
     /// then create a new clip.
-    /// Insert the new clip to the database.
+    /// The clips are compared by the hash of the text, or the hash of the png for the images.
+    /// Insert the new clip to the database, with every format in the clipboard.
+    /// The clips larger than the max clip size are skipped or truncated by the large clip policy.
     ///
     /// Will trigger a tray update event.
     #[warn(unused_must_use)]
     pub async fn update_clipboard(&mut self, app: &AppHandle) -> Result<(), Error> {
         debug!("Clipboard changed");
-        // get the current clip text
-        let clipboard_clip_text = clip_data_from_system_clipboard(app)?;
-        // if the current clip text is empty, then return
-        if clipboard_clip_text.is_empty() {
-            debug!("The clipboard text is empty, do not create a new clip");
-            return Ok(());
-        }
+        // get the current clipboard content
+        let mut clipboard_content = clip_data_from_system_clipboard(app)?;
+        let clip_type = match clipboard_content.clip_type() {
+            Some(clip_type) => clip_type,
+            None => {
+                debug!("The clipboard is empty, do not create a new clip");
+                return Ok(());
+            }
+        };
 
-        // get the current clip text
-        let current_clip = self.get_current_clip(app).await?;
-        let current_clip_text = if let Some(current_clip_text) = current_clip {
-            current_clip_text.text
-        } else {
-            Arc::new("".to_string())
+        // the clips larger than the max clip size are skipped or truncated
+        let config = app.state::<ConfigMutex>();
+        let config = config.config.lock().await;
+        let max_clip_size = config.max_clip_size;
+        let large_clip_policy = config.large_clip_policy;
+        drop(config);
+        if max_clip_size > 0 && clipboard_content.size(clip_type) > max_clip_size {
+            let truncatable = clip_type != ClipType::Image && clip_type != ClipType::File;
+            if large_clip_policy == LargeClipPolicy::Skip || !truncatable {
+                debug!("The clipboard is larger than the max clip size, do not create a new clip");
+                let event_sender = app.state::<EventSender>();
+                event_sender
+                    .send(CopyClipEvent::SendNotificationEvent(format!(
+                        "The copied content is larger than {} bytes, it is not saved.",
+                        max_clip_size
+                    )))
+                    .await;
+                return Ok(());
+            }
+
+            debug!("The clipboard is larger than the max clip size, truncate it");
+            clipboard_content.text =
+                truncate_text(&clipboard_content.text, max_clip_size as usize).to_string();
+            // the rich formats can not be cut without breaking them
+            clipboard_content.formats.clear();
+            clipboard_content.image = None;
+        }
+        // the other formats larger than the max clip size are not saved
+        if max_clip_size > 0 {
+            clipboard_content
+                .formats
+                .retain(|format| format.data.len() as u64 <= max_clip_size);
+            let image_too_large = match &clipboard_content.image {
+                Some(data) => data.len() as u64 > max_clip_size,
+                None => false,
+            };
+            if clip_type != ClipType::Image && image_too_large {
+                clipboard_content.image = None;
+            }
+        }
+        // the type changes if the rich formats are dropped
+        let clip_type = match clipboard_content.clip_type() {
+            Some(clip_type) => clip_type,
+            None => return Ok(()),
         };
+        let clipboard_hash = clipboard_content.hash(clip_type);
 
-        // if the current clip text is the same as the current clip text, then return
-        if *clipboard_clip_text == *current_clip_text {
-            debug!(
-                "The clipboard text is the same as the current clip text, do not create a new clip"
-            );
+        // if the clipboard is the same as the current clip, then return
+        if self.get_clip_hash(app, self.current_clip).await? == Some(clipboard_hash.clone()) {
+            debug!("The clipboard is the same as the current clip, do not create a new clip");
             return Ok(());
         }
 
-        // if the current clip text is the same as the last clip text, then return
+        // if the clipboard is the same as the last clip, then return
         let latest_clip_id = self.get_latest_clip_id(app).await?;
-        if self.current_clip != latest_clip_id {
-            let clip = match self.get_clip(app, latest_clip_id).await? {
-                Some(clip) => clip.text,
-                None => Arc::new("".to_string()),
-            };
-
-            if *clip == *clipboard_clip_text {
-                debug!(
-                "The clipboard text is the same as the last clip text, do not create a new clip"
-            );
-                return Ok(());
-            }
+        if self.current_clip != latest_clip_id
+            && self.get_clip_hash(app, latest_clip_id).await? == Some(clipboard_hash)
+        {
+            debug!("The clipboard is the same as the last clip, do not create a new clip");
+            return Ok(());
         }
 
-        let id = self.new_clip(app, clipboard_clip_text.clone()).await?;
+        // the formats not used by the primary representation are kept with the clip
+        let mut representations = clipboard_content.formats;
+        let id = match (clip_type, clipboard_content.image) {
+            (ClipType::Image, Some(data)) => {
+                self.new_image_clip(app, data, representations).await?
+            }
+            (_, image) => {
+                if let Some(data) = image {
+                    representations.push(ClipRepresentation::new(PNG_MIME, data));
+                }
+                if clip_type == ClipType::File {
+                    self.new_file_clip(app, clipboard_content.files, representations)
+                        .await?
+                } else {
+                    self.new_clip(app, Arc::new(clipboard_content.text), representations)
+                        .await?
+                }
+            }
+        };
         self.current_clip = Some(id);
 
         // update the tray
@@ -672,6 +1085,31 @@ impl ClipState {
         Ok(())
     }
 
+    /// Get the hash of a clip, None if the id is None or the clip does not exist
+    pub async fn get_clip_hash(
+        &self,
+        app: &AppHandle,
+        id: Option<u64>,
+    ) -> Result<Option<String>, Error> {
+        let id = match id {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let db_connection = db_connection.database_connection.lock().await;
+
+        match db_connection.query_row(
+            "SELECT hash FROM clips WHERE id = ? AND deleted_at IS NULL",
+            [id],
+            |row| row.get(0),
+        ) {
+            Ok(hash) => Ok(Some(hash)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(err) => Err(Error::GetClipDataFromDatabaseErr(id, err.to_string())),
+        }
+    }
+
     /// Get the len of whole_list_of_ids
     ///
     /// Will try lock `database_connection`.
@@ -679,7 +1117,11 @@ impl ClipState {
         let db_connection = app.state::<DatabaseStateMutex>();
         let db_connection = db_connection.database_connection.lock().await;
 
-        match db_connection.query_row("SELECT COUNT(1) FROM clips", [], |row| row.get(0)) {
+        match db_connection.query_row(
+            "SELECT COUNT(1) FROM clips WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        ) {
             Ok(res) => Ok(res),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(0),
             Err(err) => Err(Error::GetClipDataFromDatabaseErr(0, err.to_string())),
@@ -696,7 +1138,7 @@ impl ClipState {
         let db_connection = db_connection.database_connection.lock().await;
 
         match db_connection.query_row(
-            "SELECT id FROM clips ORDER BY id ASC LIMIT 1 OFFSET ?",
+            "SELECT id FROM clips WHERE deleted_at IS NULL ORDER BY id ASC LIMIT 1 OFFSET ?",
             [pos.to_string()],
             |row| row.get(0),
         ) {
@@ -939,6 +1381,14 @@ impl ClipState {
         .await?;
         debug!("Updating the favourite clips");
         self.update_favourite_clips(app, max_clip_length).await?;
+        debug!("Updating the undo item");
+        let res = app
+            .tray_handle()
+            .get_item("undo")
+            .set_enabled(!self.undo_stack.is_empty());
+        if res.is_err() {
+            return Err(Error::SetSystemTrayTitleErr(res.err().unwrap().to_string()));
+        }
 
         debug!("Finish updating the tray");
 
@@ -946,24 +1396,151 @@ impl ClipState {
     }
 }
 
-/// The current clip text in the system clipboard
-fn clip_data_from_system_clipboard(app: &AppHandle) -> Result<Arc<String>, Error> {
+/// The content of the system clipboard, every format that can be saved in a clip
+#[derive(Debug, Default)]
+struct ClipboardContent {
+    /// the paths of the copied files
+    files: Vec<String>,
+    /// the plain text
+    text: String,
+    /// the image encoded as png
+    image: Option<Vec<u8>>,
+    /// the html and rtf payloads
+    formats: Vec<ClipRepresentation>,
+}
+
+impl ClipboardContent {
+    /// The type of the primary representation, None if there is nothing can be saved as a clip
+    ///
+    /// the file list is preferred, as the file managers also put the file names as text,
+    /// then the text is preferred if the clipboard has both text and image
+    fn clip_type(&self) -> Option<ClipType> {
+        if !self.files.is_empty() {
+            return Some(ClipType::File);
+        }
+        if !self.text.is_empty() {
+            let has = |mime: &str| self.formats.iter().any(|format| format.mime == mime);
+            if has(HTML_MIME) {
+                return Some(ClipType::Html);
+            }
+            if has(RTF_MIME) {
+                return Some(ClipType::Rtf);
+            }
+            return Some(ClipType::Text);
+        }
+        match &self.image {
+            Some(data) if !data.is_empty() => Some(ClipType::Image),
+            _ => None,
+        }
+    }
+
+    /// The size of the primary representation in bytes
+    fn size(&self, clip_type: ClipType) -> u64 {
+        match clip_type {
+            ClipType::File => file_clip_text(&self.files).len() as u64,
+            ClipType::Image => self.image.as_ref().map_or(0, |data| data.len() as u64),
+            _ => self.text.len() as u64,
+        }
+    }
+
+    /// The hash of the primary representation, it is compared with the hash of the clips
+    fn hash(&self, clip_type: ClipType) -> String {
+        match clip_type {
+            ClipType::File => clip_hash(&file_clip_text(&self.files)),
+            ClipType::Image => data_hash(self.image.as_deref().unwrap_or_default()),
+            _ => clip_hash(&self.text),
+        }
+    }
+}
+
+/// The current content in the system clipboard
+///
+/// all the formats are read, the html and rtf are read only if there is a text or an image
+fn clip_data_from_system_clipboard(app: &AppHandle) -> Result<ClipboardContent, Error> {
     let clipboard_manager = app.state::<tauri_plugin_clipboard::ClipboardManager>();
+    let mut content = ClipboardContent::default();
+
+    let has_files = match clipboard_manager.has_files() {
+        Ok(has_files) => has_files,
+        Err(err) => {
+            return Err(Error::ReadFromSystemClipboardErr(err.to_string()));
+        }
+    };
+    if has_files {
+        content.files = match clipboard_manager.read_files() {
+            Ok(files) => normalize_file_paths(files),
+            Err(err) => return Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+        };
+    }
+
     let has_text = match clipboard_manager.has_text() {
         Ok(has_text) => has_text,
         Err(err) => {
             return Err(Error::ReadFromSystemClipboardErr(err.to_string()));
         }
     };
-    if !has_text {
-        // TODO unimplemented for other types of clipboard
-        return Ok(Arc::new("".to_string()));
+    if has_text {
+        content.text = match clipboard_manager.read_text() {
+            Ok(text) => text,
+            Err(err) => return Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+        };
+    }
+
+    let has_image = match clipboard_manager.has_image() {
+        Ok(has_image) => has_image,
+        Err(err) => {
+            return Err(Error::ReadFromSystemClipboardErr(err.to_string()));
+        }
+    };
+    if has_image {
+        content.image = match clipboard_manager.read_image_binary() {
+            Ok(data) if data.is_empty() => None,
+            Ok(data) => Some(data),
+            Err(err) => return Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+        };
+    }
+
+    if !content.text.is_empty() || content.image.is_some() {
+        content.formats = rich_formats_from_system_clipboard(app)?;
+    }
+
+    Ok(content)
+}
+
+/// The html and rtf payloads in the system clipboard, empty if there is none
+fn rich_formats_from_system_clipboard(app: &AppHandle) -> Result<Vec<ClipRepresentation>, Error> {
+    let clipboard_manager = app.state::<tauri_plugin_clipboard::ClipboardManager>();
+    let mut formats = Vec::new();
+
+    let has_html = match clipboard_manager.has_html() {
+        Ok(has_html) => has_html,
+        Err(err) => return Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+    };
+    if has_html {
+        match clipboard_manager.read_html() {
+            Ok(html) if !html.is_empty() => {
+                formats.push(ClipRepresentation::new(HTML_MIME, html.into_bytes()))
+            }
+            Ok(_) => (),
+            Err(err) => return Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+        };
     }
 
-    match clipboard_manager.read_text() {
-        Ok(clip) => Ok(Arc::new(clip)),
-        Err(err) => Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+    let has_rtf = match clipboard_manager.has_rtf() {
+        Ok(has_rtf) => has_rtf,
+        Err(err) => return Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+    };
+    if has_rtf {
+        match clipboard_manager.read_rtf() {
+            Ok(rtf) if !rtf.is_empty() => {
+                formats.push(ClipRepresentation::new(RTF_MIME, rtf.into_bytes()))
+            }
+            Ok(_) => (),
+            Err(err) => return Err(Error::ReadFromSystemClipboardErr(err.to_string())),
+        };
     }
+
+    Ok(formats)
 }
 
 /// chars that consider as white space
@@ -0,0 +1,203 @@
+use std::path::Path;
+
+use clip::ClipType;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{database::DatabaseStateMutex, error};
+
+/// a file in a file clip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipFile {
+    /// the full path of the file
+    pub path: String,
+    /// the file name, the last part of the path
+    pub name: String,
+    /// whether the file still exists
+    pub exists: bool,
+}
+
+/// decode the %XX escapes in a file uri,
+/// the invalid escapes are kept as they are
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                res.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        res.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&res).to_string()
+}
+
+/// turn the file uris or paths read from the clipboard into paths,
+/// the empty lines and the comments of text/uri-list are removed
+pub fn normalize_file_paths(files: Vec<String>) -> Vec<String> {
+    files
+        .iter()
+        .flat_map(|file| file.lines())
+        .map(|file| file.trim())
+        .filter(|file| !file.is_empty() && !file.starts_with('#'))
+        .map(|file| match file.strip_prefix("file://") {
+            // file://localhost/path or file:///path
+            Some(path) => {
+                let path = percent_decode(path.strip_prefix("localhost").unwrap_or(path));
+                // file:///C:/path on windows
+                let bytes = path.as_bytes();
+                if bytes.len() > 2 && bytes[0] == b'/' && bytes[2] == b':' {
+                    path[1..].to_string()
+                } else {
+                    path
+                }
+            }
+            None => file.to_string(),
+        })
+        .collect()
+}
+
+/// the file uri of a path
+fn file_uri(path: &str) -> String {
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path.replace('\\', "/"))
+    }
+}
+
+/// the text stored for a file clip, each line is a file path
+pub fn file_clip_text(paths: &[String]) -> String {
+    paths.join("\n")
+}
+
+/// the files in the text of a file clip, and whether they still exist
+pub fn clip_files(text: &str) -> Vec<ClipFile> {
+    text.lines()
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            let file = Path::new(path);
+            ClipFile {
+                path: path.to_string(),
+                name: match file.file_name() {
+                    Some(name) => name.to_string_lossy().to_string(),
+                    None => path.to_string(),
+                },
+                exists: file.exists(),
+            }
+        })
+        .collect()
+}
+
+/// the text/uri-list of a file clip, each line is a file uri
+///
+/// the missing files are left out, return an error if none of the files exists
+pub fn file_uri_list(text: &str) -> Result<String, error::Error> {
+    let uris: Vec<String> = clip_files(text)
+        .into_iter()
+        .filter(|file| file.exists)
+        .map(|file| file_uri(&file.path))
+        .collect();
+    if uris.is_empty() {
+        return Err(error::Error::WriteToSystemClipboardErr(
+            text.to_string(),
+            "none of the files exists".to_string(),
+        ));
+    }
+
+    Ok(uris.join("\r\n"))
+}
+
+/// get the files of a file clip, and whether they still exist
+///
+/// input: {
+///     id: u64, the id of the clip
+/// }
+///
+/// output: Vec<{
+///     path: String,
+///     name: String, the file name
+///     exists: bool,
+/// }>, empty if the clip is not a file clip
+#[tauri::command]
+pub async fn get_clip_files(app: AppHandle, id: u64) -> Result<Vec<ClipFile>, error::Error> {
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let (text, clip_type): (String, u8) = match db_connection.query_row(
+        "SELECT clip_text(text, compressed_text), type FROM clips WHERE id = ?",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ) {
+        Ok(res) => res,
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            return Err(error::Error::ClipNotFoundErr(id as i64))
+        }
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                id,
+                err.to_string(),
+            ))
+        }
+    };
+    drop(db_connection);
+
+    if ClipType::from(clip_type) != ClipType::File {
+        return Ok(Vec::new());
+    }
+
+    Ok(clip_files(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_escapes() {
+        assert_eq!(percent_decode("/tmp/a%20b"), "/tmp/a b");
+        assert_eq!(percent_decode("/tmp/%C3%A9t%C3%A9"), "/tmp/été");
+    }
+
+    #[test]
+    fn percent_decode_invalid_escapes() {
+        assert_eq!(percent_decode("/tmp/100%"), "/tmp/100%");
+        assert_eq!(percent_decode("/tmp/a%2"), "/tmp/a%2");
+        assert_eq!(percent_decode("/tmp/%zz"), "/tmp/%zz");
+        assert_eq!(percent_decode("%%41"), "%A");
+    }
+
+    #[test]
+    fn normalize_file_paths_uris() {
+        assert_eq!(
+            normalize_file_paths(vec![
+                "file:///tmp/a%20b.txt".to_string(),
+                "file://localhost/tmp/c.txt".to_string(),
+                "file:///C:/Users/d%20e.txt".to_string(),
+                "/tmp/plain path".to_string(),
+            ]),
+            vec![
+                "/tmp/a b.txt",
+                "/tmp/c.txt",
+                "C:/Users/d e.txt",
+                "/tmp/plain path"
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_file_paths_uri_list() {
+        assert_eq!(
+            normalize_file_paths(vec![
+                "# a comment\r\nfile:///tmp/a\r\n\r\n  file:///tmp/100%  \r\n".to_string()
+            ]),
+            vec!["/tmp/a", "/tmp/100%"]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use clip::{
+    Clip, ClipRepresentation, ClipType, HTML_MIME, PNG_MIME, RTF_MIME, TEXT_MIME, URI_LIST_MIME,
+};
+use clipboard_rs::{
+    common::{RustImage, RustImageData},
+    Clipboard, ClipboardContent, ClipboardContext,
+};
+use rusqlite::Connection;
+use tauri::{async_runtime::Mutex, AppHandle, Manager};
+
+use crate::error;
+
+use super::{file::file_uri_list, image::get_clip_image, text::get_clip_text};
+
+/// the clipboard context used to write the clips, kept for the lifetime of the app
+///
+/// on x11 the context serves the clipboard content to the other applications,
+/// so the content is lost if the context is dropped right after writing
+#[derive(Default)]
+pub struct ClipboardWriterMutex {
+    /// created when the first clip is written
+    pub clipboard: Mutex<Option<ClipboardContext>>,
+}
+
+/// store the representations of a clip besides its text and image
+#[warn(unused_must_use)]
+pub fn insert_clip_formats(
+    connection: &Connection,
+    clip_id: u64,
+    formats: &[ClipRepresentation],
+) -> Result<(), error::Error> {
+    for format in formats {
+        match connection.execute(
+            "INSERT OR REPLACE INTO clip_formats (clip_id, format, data) VALUES (?, ?, ?)",
+            rusqlite::params![clip_id, format.mime, format.data],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(error::Error::DatabaseWriteErr(err.to_string())),
+        };
+    }
+
+    Ok(())
+}
+
+/// get the representations of a clip stored besides its text and image,
+/// empty if the clip has only one format
+pub fn get_clip_formats(
+    connection: &Connection,
+    clip_id: u64,
+) -> Result<Vec<ClipRepresentation>, error::Error> {
+    let mut stmt = match connection
+        .prepare("SELECT format, data FROM clip_formats WHERE clip_id = ? ORDER BY format")
+    {
+        Ok(stmt) => stmt,
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                clip_id,
+                err.to_string(),
+            ))
+        }
+    };
+
+    let res = match stmt.query_map([clip_id], |row| {
+        Ok(ClipRepresentation {
+            mime: row.get(0)?,
+            data: row.get(1)?,
+        })
+    }) {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                clip_id,
+                err.to_string(),
+            ))
+        }
+    };
+
+    let mut formats = Vec::new();
+    for format in res {
+        match format {
+            Ok(format) => formats.push(format),
+            Err(err) => {
+                return Err(error::Error::GetClipDataFromDatabaseErr(
+                    clip_id,
+                    err.to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(formats)
+}
+
+/// get all the representations of a clip, the primary one first
+///
+/// the plain text is the full text of the clip loaded from the database, except for the image clips,
+/// as the clip may only have the preview,
+/// the png of the image clips is in the clip_images table,
+/// the file list is built from the paths, the missing files are left out,
+/// and the others are in the clip_formats table
+pub fn get_clip_representations(
+    connection: &Connection,
+    clip: &Clip,
+) -> Result<Vec<ClipRepresentation>, error::Error> {
+    let text = match get_clip_text(connection, clip.id)? {
+        Some(text) => text,
+        None => return Err(error::Error::ClipNotFoundErr(clip.id as i64)),
+    };
+
+    let mut representations = Vec::new();
+    match clip.clip_type {
+        ClipType::Image => match get_clip_image(connection, clip.id)? {
+            Some(image) => representations.push(ClipRepresentation::new(PNG_MIME, image.data)),
+            None => {
+                return Err(error::Error::GetClipDataFromDatabaseErr(
+                    clip.id,
+                    "the image of the clip is missing".to_string(),
+                ))
+            }
+        },
+        ClipType::File => {
+            representations.push(ClipRepresentation::new(
+                URI_LIST_MIME,
+                file_uri_list(&text)?.into_bytes(),
+            ));
+            representations.push(ClipRepresentation::new(TEXT_MIME, text.into_bytes()));
+        }
+        _ => representations.push(ClipRepresentation::new(TEXT_MIME, text.into_bytes())),
+    }
+    representations.extend(get_clip_formats(connection, clip.id)?);
+
+    // the sort is stable, the order of the others is kept
+    let primary = clip.clip_type.mime();
+    representations.sort_by_key(|representation| representation.mime != primary);
+
+    Ok(representations)
+}
+
+/// write all the representations of a clip to the clipboard at the same time,
+/// so every application can paste the format it understands
+///
+/// the formats unknown to the clipboard are written with their mime type
+pub async fn write_representations_to_clipboard(
+    app: &AppHandle,
+    text: &str,
+    representations: &[ClipRepresentation],
+) -> Result<(), error::Error> {
+    let mut contents = Vec::new();
+    for representation in representations {
+        let data = &representation.data;
+        let content = match representation.mime.as_str() {
+            TEXT_MIME => ClipboardContent::Text(String::from_utf8_lossy(data).to_string()),
+            HTML_MIME => ClipboardContent::Html(String::from_utf8_lossy(data).to_string()),
+            RTF_MIME => ClipboardContent::Rtf(String::from_utf8_lossy(data).to_string()),
+            PNG_MIME => match RustImageData::from_bytes(data) {
+                Ok(image) => ClipboardContent::Image(image),
+                Err(err) => {
+                    return Err(error::Error::WriteToSystemClipboardErr(
+                        text.to_string(),
+                        err.to_string(),
+                    ))
+                }
+            },
+            URI_LIST_MIME => ClipboardContent::Files(
+                String::from_utf8_lossy(data)
+                    .lines()
+                    .map(|uri| uri.to_string())
+                    .collect(),
+            ),
+            mime => ClipboardContent::Other(mime.to_string(), data.clone()),
+        };
+        contents.push(content);
+    }
+
+    let clipboard_writer = app.state::<ClipboardWriterMutex>();
+    let mut clipboard_writer = clipboard_writer.clipboard.lock().await;
+    let clipboard = match clipboard_writer.take() {
+        Some(clipboard) => clipboard,
+        None => match ClipboardContext::new() {
+            Ok(clipboard) => clipboard,
+            Err(err) => {
+                return Err(error::Error::WriteToSystemClipboardErr(
+                    text.to_string(),
+                    err.to_string(),
+                ))
+            }
+        },
+    };
+    let res = clipboard.set(contents);
+    // keep the context, it serves the content until the next write
+    *clipboard_writer = Some(clipboard);
+    match res {
+        Ok(_) => Ok(()),
+        Err(err) => Err(error::Error::WriteToSystemClipboardErr(
+            text.to_string(),
+            err.to_string(),
+        )),
+    }
+}
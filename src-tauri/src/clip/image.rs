@@ -0,0 +1,159 @@
+use base64::Engine;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{database::DatabaseStateMutex, error};
+
+/// the png signature, the first 8 bytes of every png file
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// the png of an image clip
+#[derive(Debug, Clone)]
+pub struct ClipImage {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// the image clip sent to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipImageData {
+    pub width: u32,
+    pub height: u32,
+    /// the size of the png in bytes
+    pub size: u64,
+    /// the png encoded in base64
+    pub data: String,
+}
+
+/// read the width and height from the IHDR chunk of a png,
+/// return None if the data is not a png
+pub fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    // signature, chunk length, chunk type "IHDR", width, height
+    if data.len() < 24 || data[..8] != PNG_SIGNATURE || &data[12..16] != b"IHDR" {
+        return None;
+    }
+
+    let width = u32::from_be_bytes([data[16], data[17], data[18], data[19]]);
+    let height = u32::from_be_bytes([data[20], data[21], data[22], data[23]]);
+    Some((width, height))
+}
+
+/// the text stored for an image clip, shown in the tray and used by the search
+pub fn image_clip_text(width: u32, height: u32, size: usize) -> String {
+    format!("[Image {}x{}, {} KB]", width, height, size.div_ceil(1024))
+}
+
+/// store the png of an image clip
+#[warn(unused_must_use)]
+pub fn insert_clip_image(
+    connection: &Connection,
+    clip_id: u64,
+    image: &ClipImage,
+) -> Result<(), error::Error> {
+    match connection.execute(
+        "INSERT OR REPLACE INTO clip_images (clip_id, data, width, height, size)
+        VALUES (?, ?, ?, ?, ?)",
+        rusqlite::params![
+            clip_id,
+            image.data,
+            image.width,
+            image.height,
+            image.data.len() as u64
+        ],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(error::Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// get the png of an image clip, None if the clip has no image
+pub fn get_clip_image(
+    connection: &Connection,
+    clip_id: u64,
+) -> Result<Option<ClipImage>, error::Error> {
+    match connection.query_row(
+        "SELECT data, width, height FROM clip_images WHERE clip_id = ?",
+        [clip_id],
+        |row| {
+            Ok(ClipImage {
+                data: row.get(0)?,
+                width: row.get(1)?,
+                height: row.get(2)?,
+            })
+        },
+    ) {
+        Ok(image) => Ok(Some(image)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(err) => Err(error::Error::GetClipDataFromDatabaseErr(
+            clip_id,
+            err.to_string(),
+        )),
+    }
+}
+
+/// get the png of an image clip
+///
+/// input: {
+///     id: u64, the id of the clip
+/// }
+///
+/// output: {
+///     width: u32,
+///     height: u32,
+///     size: u64, the size of the png in bytes
+///     data: String, the png encoded in base64
+/// }, or null if the clip is not an image
+#[tauri::command]
+pub async fn get_clip_image_data(
+    app: AppHandle,
+    id: u64,
+) -> Result<Option<ClipImageData>, error::Error> {
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let image = get_clip_image(&db_connection, id)?;
+    drop(db_connection);
+
+    Ok(image.map(|image| ClipImageData {
+        width: image.width,
+        height: image.height,
+        size: image.data.len() as u64,
+        data: base64::engine::general_purpose::STANDARD.encode(&image.data),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the signature and the start of the IHDR chunk of a png
+    fn png_header(chunk_type: &[u8; 4], width: u32, height: u32) -> Vec<u8> {
+        let mut data = PNG_SIGNATURE.to_vec();
+        data.extend_from_slice(&13u32.to_be_bytes());
+        data.extend_from_slice(chunk_type);
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn png_dimensions_of_png() {
+        assert_eq!(
+            png_dimensions(&png_header(b"IHDR", 640, 480)),
+            Some((640, 480))
+        );
+    }
+
+    #[test]
+    fn png_dimensions_of_other_data() {
+        let data = png_header(b"IHDR", 640, 480);
+        assert_eq!(png_dimensions(&data[..23]), None);
+        assert_eq!(png_dimensions(&png_header(b"IDAT", 640, 480)), None);
+
+        let mut data = data;
+        data[1] = b'J';
+        assert_eq!(png_dimensions(&data), None);
+        assert_eq!(png_dimensions(b"GIF89a"), None);
+    }
+}
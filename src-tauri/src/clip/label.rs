@@ -0,0 +1,150 @@
+use tauri::AppHandle;
+
+use crate::{error, event::EventSender};
+
+use super::clip_data::ClipStateMutex;
+
+/// get all the labels, ordered by name
+///
+/// output: Vec<String>
+#[tauri::command]
+pub async fn get_all_labels(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+) -> Result<Vec<String>, error::Error> {
+    let clip_state = clip_state.clip_state.lock().await;
+    clip_state.get_all_labels(&app).await
+}
+
+/// get the labels of a clip, ordered by name
+///
+/// input: {
+///     id: u64, the id of the clip
+/// }
+///
+/// output: Vec<String>
+#[tauri::command]
+pub async fn get_clip_labels(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    id: u64,
+) -> Result<Vec<String>, error::Error> {
+    let clip_state = clip_state.clip_state.lock().await;
+    let labels = clip_state.get_clip_labels(&app, id).await?;
+
+    Ok(labels.unwrap_or_default())
+}
+
+/// create a new label
+///
+/// input: {
+///     label: String, the name of the new label
+/// }
+#[tauri::command]
+pub async fn create_label(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    label: String,
+) -> Result<(), error::Error> {
+    let mut clip_state = clip_state.clip_state.lock().await;
+    clip_state.create_label(&app, &label).await
+}
+
+/// rename a label, the default labels pinned and favourite can not be renamed
+///
+/// input: {
+///     label: String, the current name of the label
+///     newlabel: String, the new name of the label
+/// }
+#[tauri::command]
+pub async fn rename_label(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    event_sender: tauri::State<'_, EventSender>,
+    label: String,
+    newlabel: String,
+) -> Result<(), error::Error> {
+    let mut clip_state = clip_state.clip_state.lock().await;
+    clip_state.rename_label(&app, &label, &newlabel).await?;
+    drop(clip_state);
+
+    event_sender
+        .send(crate::event::CopyClipEvent::SendNotificationEvent(format!(
+            "Label {} renamed to {}.",
+            label, newlabel
+        )))
+        .await;
+
+    Ok(())
+}
+
+/// delete a label and remove it from all the clips,
+/// the default labels pinned and favourite can not be deleted
+///
+/// input: {
+///     label: String, the name of the label
+/// }
+#[tauri::command]
+pub async fn delete_label(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    event_sender: tauri::State<'_, EventSender>,
+    label: String,
+) -> Result<(), error::Error> {
+    let mut clip_state = clip_state.clip_state.lock().await;
+    clip_state.delete_label(&app, &label).await?;
+    drop(clip_state);
+
+    event_sender
+        .send(crate::event::CopyClipEvent::SendNotificationEvent(format!(
+            "Label {} deleted.",
+            label
+        )))
+        .await;
+
+    Ok(())
+}
+
+/// add an existing label to a clip
+///
+/// input: {
+///     id: u64, the id of the clip
+///     label: String, the name of the label
+/// }
+#[tauri::command]
+pub async fn add_label_to_clip(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    id: u64,
+    label: String,
+) -> Result<(), error::Error> {
+    let mut clip_state = clip_state.clip_state.lock().await;
+    if !clip_state.label_exists(&app, &label).await? {
+        return Err(error::Error::LabelErr(format!(
+            "label {} does not exist",
+            label
+        )));
+    }
+    if clip_state.get_clip(&app, Some(id)).await?.is_none() {
+        return Err(error::Error::ClipNotFoundErr(id as i64));
+    }
+
+    clip_state.change_clip_label(&app, id, &label, true).await
+}
+
+/// remove a label from a clip
+///
+/// input: {
+///     id: u64, the id of the clip
+///     label: String, the name of the label
+/// }
+#[tauri::command]
+pub async fn remove_label_from_clip(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    id: u64,
+    label: String,
+) -> Result<(), error::Error> {
+    let mut clip_state = clip_state.clip_state.lock().await;
+    clip_state.change_clip_label(&app, id, &label, false).await
+}
@@ -1,15 +1,31 @@
 pub mod clip_data;
+pub mod file;
+pub mod format;
+pub mod image;
+pub mod label;
 pub mod monitor;
+pub mod order;
+pub mod query;
+pub mod retention;
+pub mod revision;
 pub mod search;
+pub mod statistics;
+pub mod text;
+pub mod trash;
+pub mod undo;
 
 use tauri::{AppHandle, Manager};
 
 use crate::{
+    database::DatabaseStateMutex,
     error::Error,
     event::{CopyClipEvent, EventSender},
 };
 
-use self::clip_data::ClipStateMutex;
+use self::{
+    clip_data::ClipStateMutex,
+    format::{get_clip_representations, write_representations_to_clipboard},
+};
 
 /// get the unix epoch timestamp in seconds
 pub fn get_system_timestamp() -> i64 {
@@ -20,16 +36,21 @@ pub fn get_system_timestamp() -> i64 {
     unix_epoch.as_secs() as i64
 }
 
-/// copy the clip to the clipboard
-pub fn copy_clip_to_clipboard_in(clip: &clip::Clip, app: &AppHandle) -> Result<(), Error> {
-    let clipboard_manager = app.state::<tauri_plugin_clipboard::ClipboardManager>();
-    match clipboard_manager.write_text((*clip.text).clone()) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(Error::WriteToSystemClipboardErr(
-            (*clip.text).clone(),
-            e.to_string(),
-        )),
-    }
+/// copy the clip to the clipboard, with all its representations
+///
+/// the representations are loaded from the database if the clip does not have them
+pub async fn copy_clip_to_clipboard_in(clip: &clip::Clip, app: &AppHandle) -> Result<(), Error> {
+    let representations = if clip.representations.is_empty() {
+        let db_connection = app.state::<DatabaseStateMutex>();
+        let db_connection = db_connection.database_connection.lock().await;
+        let representations = get_clip_representations(&db_connection, clip)?;
+        drop(db_connection);
+        representations
+    } else {
+        clip.representations.clone()
+    };
+
+    write_representations_to_clipboard(app, &clip.text, &representations).await
 }
 
 /// tell if the clip is pinned
@@ -66,7 +87,7 @@ pub async fn copy_clip_to_clipboard(
         return Err("Clip not found.".to_string());
     }
     let clip_data = clip_data.unwrap();
-    let res = copy_clip_to_clipboard_in(&clip_data, &app);
+    let res = copy_clip_to_clipboard_in(&clip_data, &app).await;
     if let Err(err) = res {
         return Err(err.message());
     }
@@ -80,7 +101,7 @@ pub async fn copy_clip_to_clipboard(
     Ok(())
 }
 
-/// Delete a normal clip from the database
+/// Move a clip to the trash
 #[tauri::command]
 pub async fn delete_clip_from_database(
     app: tauri::AppHandle,
@@ -99,7 +120,7 @@ pub async fn delete_clip_from_database(
     event_sender.send(CopyClipEvent::RebuildTrayMenuEvent).await;
     event_sender
         .send(CopyClipEvent::SendNotificationEvent(
-            "Clip deleted from database.".to_string(),
+            "Clip moved to the trash, it can be undone from the tray.".to_string(),
         ))
         .await;
 
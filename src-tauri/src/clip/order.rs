@@ -0,0 +1,245 @@
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::error;
+
+/// the key the search results are ordered by,
+/// the clips with the same key are ordered by id in the same direction
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SearchOrder {
+    #[default]
+    Id,
+    Time,
+    Size,
+    /// the preview of the text, the first chars of the text
+    Text,
+    /// the fuzzy score for the fuzzy search, the bm25 relevance for the full text search,
+    /// the other search methods have no score and are ordered by id
+    Score,
+}
+
+impl SearchOrder {
+    /// the sql expression of the key,
+    /// the score depends on the search method, the id is used if the method has no score
+    fn key_expr(&self) -> &'static str {
+        match self {
+            SearchOrder::Id | SearchOrder::Score => "clips.id",
+            SearchOrder::Time => "clips.timestamp",
+            SearchOrder::Size => "clips.size",
+            SearchOrder::Text => "clips.preview",
+        }
+    }
+}
+
+impl TryFrom<&str> for SearchOrder {
+    type Error = error::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "id" => Ok(SearchOrder::Id),
+            "time" => Ok(SearchOrder::Time),
+            "size" => Ok(SearchOrder::Size),
+            "text" => Ok(SearchOrder::Text),
+            "score" | "fuzzy_score" | "relevance" => Ok(SearchOrder::Score),
+            _ => Err(error::Error::InvalidSearchQueryErr(format!(
+                "unknown order {}, it should be one of id, time, size, text and score",
+                value
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for SearchOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchOrder::Id => write!(f, "id"),
+            SearchOrder::Time => write!(f, "time"),
+            SearchOrder::Size => write!(f, "size"),
+            SearchOrder::Text => write!(f, "text"),
+            SearchOrder::Score => write!(f, "score"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OrderDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl OrderDirection {
+    fn sql(&self) -> &'static str {
+        match self {
+            OrderDirection::Asc => "ASC",
+            OrderDirection::Desc => "DESC",
+        }
+    }
+
+    /// the comparison of the rows after the cursor
+    fn after(&self) -> &'static str {
+        match self {
+            OrderDirection::Asc => ">",
+            OrderDirection::Desc => "<",
+        }
+    }
+}
+
+impl TryFrom<&str> for OrderDirection {
+    type Error = error::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "asc" => Ok(OrderDirection::Asc),
+            "desc" => Ok(OrderDirection::Desc),
+            _ => Err(error::Error::InvalidSearchQueryErr(format!(
+                "unknown order direction {}, it should be asc or desc",
+                value
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for OrderDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderDirection::Asc => write!(f, "asc"),
+            OrderDirection::Desc => write!(f, "desc"),
+        }
+    }
+}
+
+/// the value of the order key in the cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum CursorValue {
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+/// the position of the last clip of a page,
+/// the next page starts after it
+///
+/// the cursor is sent to the frontend as a json string,
+/// it is only valid for the same order and direction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchCursor {
+    order: String,
+    direction: String,
+    value: CursorValue,
+    id: u64,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// the order of a search, and the cursor of the page
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchOrdering {
+    pub order: SearchOrder,
+    pub direction: OrderDirection,
+    pub cursor: Option<SearchCursor>,
+}
+
+impl SearchOrdering {
+    /// parse the order, the direction and the cursor sent by the frontend,
+    /// the default is the id descending from the first page
+    pub fn parse(
+        order: Option<&str>,
+        direction: Option<&str>,
+        cursor: Option<&str>,
+    ) -> Result<Self, error::Error> {
+        let order = match order {
+            Some(order) => SearchOrder::try_from(order)?,
+            None => SearchOrder::default(),
+        };
+        let direction = match direction {
+            Some(direction) => OrderDirection::try_from(direction)?,
+            None => OrderDirection::default(),
+        };
+
+        let cursor = match cursor {
+            Some(cursor) if !cursor.is_empty() => {
+                let cursor: SearchCursor = match serde_json::from_str(cursor) {
+                    Ok(cursor) => cursor,
+                    Err(err) => {
+                        return Err(error::Error::InvalidSearchQueryErr(format!(
+                            "invalid cursor, error message: {}",
+                            err
+                        )))
+                    }
+                };
+                if cursor.order != order.to_string() || cursor.direction != direction.to_string() {
+                    return Err(error::Error::InvalidSearchQueryErr(
+                        "the cursor is for another order, start the search again".to_string(),
+                    ));
+                }
+                Some(cursor)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            order,
+            direction,
+            cursor,
+        })
+    }
+
+    /// the sql expression selected as `sort_key`, when the search method has no score
+    pub fn key_expr(&self) -> &'static str {
+        self.order.key_expr()
+    }
+
+    /// the condition on `sort_key` and `id` matching the rows after the cursor,
+    /// and the values to bind to it in order
+    ///
+    /// None if this is the first page
+    pub fn cursor_condition(&self) -> Option<(String, Vec<Value>)> {
+        let cursor = self.cursor.as_ref()?;
+        let value = match &cursor.value {
+            CursorValue::Integer(value) => Value::Integer(*value),
+            CursorValue::Real(value) => Value::Real(*value),
+            CursorValue::Text(value) => Value::Text(value.clone()),
+        };
+        let after = self.direction.after();
+
+        Some((
+            format!(
+                "(sort_key {} ? OR (sort_key = ? AND id {} ?))",
+                after, after
+            ),
+            vec![value.clone(), value, Value::Integer(cursor.id as i64)],
+        ))
+    }
+
+    /// the ORDER BY clause on `sort_key` and `id`
+    pub fn order_by(&self) -> String {
+        format!(
+            "sort_key {}, id {}",
+            self.direction.sql(),
+            self.direction.sql()
+        )
+    }
+
+    /// the cursor after the row, None if the sort key can not be in a cursor
+    pub fn cursor_after(&self, sort_key: Value, id: u64) -> Option<SearchCursor> {
+        let value = match sort_key {
+            Value::Integer(value) => CursorValue::Integer(value),
+            Value::Real(value) => CursorValue::Real(value),
+            Value::Text(value) => CursorValue::Text(value),
+            Value::Null | Value::Blob(_) => return None,
+        };
+
+        Some(SearchCursor {
+            order: self.order.to_string(),
+            direction: self.direction.to_string(),
+            value,
+            id,
+        })
+    }
+}
@@ -0,0 +1,435 @@
+use chrono::NaiveDate;
+use clip::{ClipType, LabelFilter, LabelMatch};
+use rusqlite::types::Value;
+
+use crate::error;
+
+/// the text of the clip in the sql conditions, the full text,
+/// or the preview if the text is compressed, the filters never decompress a clip
+const CLIP_TEXT: &str = "clips.text";
+
+/// a search query parsed from the user input
+///
+/// the terms are separated by spaces, all of them must match:
+/// - `label:work`, the clip has the label, quote the label if it has spaces, `label:"my label"`
+/// - `type:html`, the clip is of the type, one of text, image, file, html and rtf,
+///   with several types the clip can be of any of them
+/// - `after:2024-05-01`, the clip is created on or after the day, in the local time
+/// - `before:2024-06-01`, the clip is created before the day, in the local time
+/// - `"exact phrase"`, the text contains the phrase, case sensitive
+/// - `-password` or `-"a phrase"`, the text does not contain the word or the phrase, ascii case insensitive
+/// - `-label:work` or `-type:image`, the clip does not have the label or the type
+/// - the other words are joined by a space and matched by the search method,
+///   a word with an unknown key, like `https://example.com`, is a plain word
+///
+/// the texts are matched against the preview of the compressed large clips
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    /// the text matched by the search method
+    pub text: String,
+    pub phrases: Vec<String>,
+    pub excluded_texts: Vec<String>,
+    /// the clip should match all the filters, the `label:` terms are one filter
+    /// with the `LabelMatch::All` mode
+    pub label_filters: Vec<LabelFilter>,
+    pub types: Vec<ClipType>,
+    pub excluded_types: Vec<ClipType>,
+    /// unix timestamp in seconds, inclusive
+    pub after: Option<i64>,
+    /// unix timestamp in seconds, exclusive
+    pub before: Option<i64>,
+}
+
+impl SearchQuery {
+    /// parse the user input, see `SearchQuery` for the syntax
+    pub fn parse(input: &str) -> Result<Self, error::Error> {
+        let mut query = Self::default();
+        let mut words = Vec::new();
+        let mut label_filter = LabelFilter::default();
+
+        for term in split_terms(input) {
+            let (negated, body) = match term.strip_prefix('-') {
+                Some(body) if !body.is_empty() => (true, body),
+                _ => (false, term.as_str()),
+            };
+
+            if body.starts_with('"') {
+                let phrase = unquote(body);
+                if phrase.is_empty() {
+                    continue;
+                }
+                if negated {
+                    query.excluded_texts.push(phrase.to_string());
+                } else {
+                    query.phrases.push(phrase.to_string());
+                }
+                continue;
+            }
+
+            let filter = match body.split_once(':') {
+                Some((key, value)) if is_filter_key(key) && !value.is_empty() => {
+                    Some((key, unquote(value)))
+                }
+                _ => None,
+            };
+            let (key, value) = match filter {
+                Some(filter) => filter,
+                None => {
+                    if negated {
+                        query.excluded_texts.push(body.to_string());
+                    } else {
+                        words.push(term);
+                    }
+                    continue;
+                }
+            };
+
+            match (key, negated) {
+                ("label", false) => label_filter.include.push(value.to_string()),
+                ("label", true) => label_filter.exclude.push(value.to_string()),
+                ("type", false) => query.types.push(parse_clip_type(value)?),
+                ("type", true) => query.excluded_types.push(parse_clip_type(value)?),
+                ("after", false) => query.after = Some(parse_day(value)?),
+                ("before", false) => query.before = Some(parse_day(value)?),
+                _ => {
+                    return Err(error::Error::InvalidSearchQueryErr(format!(
+                        "{} can not be negated",
+                        key
+                    )))
+                }
+            }
+        }
+
+        query.text = words.join(" ");
+        if !label_filter.is_empty() {
+            query.label_filters.push(label_filter);
+        }
+
+        Ok(query)
+    }
+
+    /// the sql conditions of the filters, all of them must match,
+    /// and the values to bind to them in order
+    ///
+    /// the conditions use the columns of the clips table as `clips.column`
+    pub fn filter_conditions(&self) -> (Vec<String>, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        for phrase in &self.phrases {
+            conditions.push(format!("instr({}, ?) > 0", CLIP_TEXT));
+            values.push(Value::Text(phrase.clone()));
+        }
+
+        for text in &self.excluded_texts {
+            conditions.push(format!("{} NOT LIKE ? ESCAPE '\\'", CLIP_TEXT));
+            values.push(Value::Text(format!("%{}%", escape_like(text))));
+        }
+
+        for label_filter in &self.label_filters {
+            let (label_conditions, label_values) = label_filter_conditions(label_filter);
+            conditions.extend(label_conditions);
+            values.extend(label_values);
+        }
+
+        if !self.types.is_empty() {
+            conditions.push(format!(
+                "clips.type IN ({})",
+                vec!["?"; self.types.len()].join(", ")
+            ));
+            for clip_type in &self.types {
+                values.push(Value::Integer(u8::from(*clip_type) as i64));
+            }
+        }
+
+        if !self.excluded_types.is_empty() {
+            conditions.push(format!(
+                "clips.type NOT IN ({})",
+                vec!["?"; self.excluded_types.len()].join(", ")
+            ));
+            for clip_type in &self.excluded_types {
+                values.push(Value::Integer(u8::from(*clip_type) as i64));
+            }
+        }
+
+        if let Some(after) = self.after {
+            conditions.push("clips.timestamp >= ?".to_string());
+            values.push(Value::Integer(after));
+        }
+
+        if let Some(before) = self.before {
+            conditions.push("clips.timestamp < ?".to_string());
+            values.push(Value::Integer(before));
+        }
+
+        (conditions, values)
+    }
+}
+
+/// the sql conditions of the label filter, all of them must match,
+/// and the values to bind to them in order
+pub fn label_filter_conditions(label_filter: &LabelFilter) -> (Vec<String>, Vec<Value>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+
+    if !label_filter.include.is_empty() {
+        let placeholders = vec!["?"; label_filter.include.len()].join(", ");
+        for label in &label_filter.include {
+            values.push(Value::Text(label.clone()));
+        }
+        match label_filter.mode {
+            LabelMatch::Any => conditions.push(format!(
+                "clips.id IN (SELECT clip_id FROM clip_labels WHERE label IN ({}))",
+                placeholders
+            )),
+            LabelMatch::All => {
+                conditions.push(format!(
+                    "clips.id IN (SELECT clip_id FROM clip_labels WHERE label IN ({})
+                        GROUP BY clip_id HAVING COUNT(DISTINCT label) = ?)",
+                    placeholders
+                ));
+                // the same label can be included twice
+                let mut labels = label_filter.include.clone();
+                labels.sort();
+                labels.dedup();
+                values.push(Value::Integer(labels.len() as i64));
+            }
+        }
+    }
+
+    if !label_filter.exclude.is_empty() {
+        conditions.push(format!(
+            "clips.id NOT IN (SELECT clip_id FROM clip_labels WHERE label IN ({}))",
+            vec!["?"; label_filter.exclude.len()].join(", ")
+        ));
+        for label in &label_filter.exclude {
+            values.push(Value::Text(label.clone()));
+        }
+    }
+
+    (conditions, values)
+}
+
+/// split the input by the spaces, the spaces inside double quotes do not split,
+/// the quotes are kept in the terms
+fn split_terms(input: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c.is_whitespace() && !quoted {
+            if !term.is_empty() {
+                terms.push(std::mem::take(&mut term));
+            }
+            continue;
+        }
+        term.push(c);
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+
+    terms
+}
+
+/// remove the double quotes around the value, the closing quote can be missing
+fn unquote(value: &str) -> &str {
+    match value.strip_prefix('"') {
+        Some(value) => value.strip_suffix('"').unwrap_or(value),
+        None => value,
+    }
+}
+
+fn is_filter_key(key: &str) -> bool {
+    matches!(key, "label" | "type" | "after" | "before")
+}
+
+fn parse_clip_type(value: &str) -> Result<ClipType, error::Error> {
+    match value.to_lowercase().as_str() {
+        "text" => Ok(ClipType::Text),
+        "image" => Ok(ClipType::Image),
+        "file" => Ok(ClipType::File),
+        "html" => Ok(ClipType::Html),
+        "rtf" => Ok(ClipType::Rtf),
+        _ => Err(error::Error::InvalidSearchQueryErr(format!(
+            "unknown clip type {}, it should be one of text, image, file, html and rtf",
+            value
+        ))),
+    }
+}
+
+/// the unix timestamp of the start of the day YYYY-MM-DD in the local time
+fn parse_day(value: &str) -> Result<i64, error::Error> {
+    let date = match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(err) => {
+            return Err(error::Error::InvalidSearchQueryErr(format!(
+                "invalid date {}, it should be YYYY-MM-DD, error message: {}",
+                value, err
+            )))
+        }
+    };
+
+    match date
+        .and_time(chrono::NaiveTime::MIN)
+        .and_local_timezone(chrono::Local)
+        .earliest()
+    {
+        Some(time) => Ok(time.timestamp()),
+        None => Err(error::Error::InvalidSearchQueryErr(format!(
+            "the date {} does not exist in the local time zone",
+            value
+        ))),
+    }
+}
+
+/// escape the LIKE wildcards, the escape char is `\`
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_terms_keeps_quoted_spaces() {
+        assert_eq!(
+            split_terms(r#"  label:"my label" foo   "a b" -x "#),
+            vec![r#"label:"my label""#, "foo", r#""a b""#, "-x"]
+        );
+    }
+
+    #[test]
+    fn split_terms_unterminated_quote() {
+        assert_eq!(split_terms(r#"foo "bar baz"#), vec!["foo", r#""bar baz"#]);
+    }
+
+    #[test]
+    fn parse_all_kinds_of_terms() {
+        let query = SearchQuery::parse(
+            r#"hello label:"my label" -label:work type:HTML -type:image after:2024-05-01 "exact phrase" -password -"a phrase" https://example.com"#,
+        )
+        .unwrap();
+
+        assert_eq!(query.text, "hello https://example.com");
+        assert_eq!(query.phrases, vec!["exact phrase"]);
+        assert_eq!(query.excluded_texts, vec!["password", "a phrase"]);
+        assert_eq!(
+            query.label_filters,
+            vec![LabelFilter {
+                include: vec!["my label".to_string()],
+                exclude: vec!["work".to_string()],
+                mode: LabelMatch::All,
+            }]
+        );
+        assert_eq!(query.types, vec![ClipType::Html]);
+        assert_eq!(query.excluded_types, vec![ClipType::Image]);
+        assert!(query.after.is_some());
+        assert_eq!(query.before, None);
+    }
+
+    #[test]
+    fn parse_unterminated_quote() {
+        let query = SearchQuery::parse(r#"foo "bar baz"#).unwrap();
+        assert_eq!(query.text, "foo");
+        assert_eq!(query.phrases, vec!["bar baz"]);
+
+        let query = SearchQuery::parse(r#"label:"my label"#).unwrap();
+        assert_eq!(query.label_filters[0].include, vec!["my label"]);
+    }
+
+    #[test]
+    fn parse_plain_words() {
+        // a lone dash, an empty filter value and an unknown key are plain words
+        let query = SearchQuery::parse("- label: key:value").unwrap();
+        assert_eq!(query.text, "- label: key:value");
+        assert!(query.excluded_texts.is_empty());
+        assert!(query.label_filters.is_empty());
+    }
+
+    #[test]
+    fn parse_negated_filter_keys() {
+        for input in ["-after:2024-01-01", "-before:2024-01-01"] {
+            assert!(matches!(
+                SearchQuery::parse(input),
+                Err(error::Error::InvalidSearchQueryErr(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_invalid_values() {
+        assert!(SearchQuery::parse("type:video").is_err());
+        assert!(SearchQuery::parse("after:2024-13-01").is_err());
+        assert!(SearchQuery::parse("before:yesterday").is_err());
+    }
+
+    #[test]
+    fn filter_conditions_escape_like() {
+        let query = SearchQuery::parse(r"-100%_\").unwrap();
+        let (conditions, values) = query.filter_conditions();
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(values, vec![Value::Text(r"%100\%\_\\%".to_string())]);
+    }
+
+    #[test]
+    fn label_filter_conditions_all() {
+        let label_filter = LabelFilter {
+            include: vec!["a".to_string(), "b".to_string(), "a".to_string()],
+            exclude: Vec::new(),
+            mode: LabelMatch::All,
+        };
+        let (conditions, values) = label_filter_conditions(&label_filter);
+
+        assert_eq!(conditions.len(), 1);
+        assert!(conditions[0].contains("label IN (?, ?, ?)"));
+        assert!(conditions[0].contains("HAVING COUNT(DISTINCT label) = ?"));
+        assert_eq!(
+            values,
+            vec![
+                Value::Text("a".to_string()),
+                Value::Text("b".to_string()),
+                Value::Text("a".to_string()),
+                // the label included twice is counted once
+                Value::Integer(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn label_filter_conditions_any_and_exclude() {
+        let label_filter = LabelFilter {
+            include: vec!["a".to_string()],
+            exclude: vec!["b".to_string(), "c".to_string()],
+            mode: LabelMatch::Any,
+        };
+        let (conditions, values) = label_filter_conditions(&label_filter);
+
+        assert_eq!(conditions.len(), 2);
+        assert!(conditions[0].contains("clips.id IN"));
+        assert!(!conditions[0].contains("HAVING"));
+        assert!(conditions[1].contains("clips.id NOT IN"));
+        assert!(conditions[1].contains("label IN (?, ?)"));
+        assert_eq!(
+            values,
+            vec![
+                Value::Text("a".to_string()),
+                Value::Text("b".to_string()),
+                Value::Text("c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn label_filter_conditions_empty() {
+        let (conditions, values) = label_filter_conditions(&LabelFilter::default());
+        assert!(conditions.is_empty());
+        assert!(values.is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use log::{debug, warn};
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    config::{Config, ConfigMutex},
+    database::DatabaseStateMutex,
+    error::Error,
+    event::{event_sender, CopyClipEvent},
+};
+
+use super::{clip_data::ClipStateMutex, get_system_timestamp};
+
+/// how often the retention policy is applied in the background, in seconds
+const PRUNE_INTERVAL: u64 = 60 * 60;
+
+/// the limits on the clips history, 0 means no limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    /// the max number of clips to keep
+    pub max_clip_count: u64,
+    /// the max age of a clip in days
+    pub max_clip_age_days: u64,
+    /// the max total size of the text of all clips in bytes
+    pub max_total_text_bytes: u64,
+    /// the clips with any of these labels are never deleted
+    pub exempt_labels: Vec<String>,
+    /// the days a clip stays in the trash before it is deleted
+    pub trash_auto_empty_days: u64,
+}
+
+impl RetentionPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_clip_count: config.max_clip_count,
+            max_clip_age_days: config.max_clip_age_days,
+            max_total_text_bytes: config.max_total_text_bytes,
+            exempt_labels: config.retention_exempt_labels.clone(),
+            trash_auto_empty_days: config.trash_auto_empty_days,
+        }
+    }
+
+    /// there is no limit, nothing will be pruned
+    pub fn is_unlimited(&self) -> bool {
+        self.max_clip_count == 0
+            && self.max_clip_age_days == 0
+            && self.max_total_text_bytes == 0
+            && self.trash_auto_empty_days == 0
+    }
+
+    /// the sql condition matching the clips that can be deleted,
+    /// the labels need to be bound in the same order as `exempt_labels`
+    fn not_exempt_condition(&self) -> String {
+        if self.exempt_labels.is_empty() {
+            return "1".to_string();
+        }
+
+        format!(
+            "id NOT IN (SELECT clip_id FROM clip_labels WHERE label IN ({}))",
+            vec!["?"; self.exempt_labels.len()].join(", ")
+        )
+    }
+}
+
+/// delete the clips breaking the retention policy, the oldest clips are deleted first
+///
+/// the trash is emptied first, then the limits are applied in the order of age, count and total text size,
+/// the clips with an exempt label still count towards the limits, but are never deleted,
+/// the clips in the trash count towards the limits, and can be deleted by them
+///
+/// return the number of clips deleted
+#[warn(unused_must_use)]
+pub fn prune_clips(
+    connection: &Connection,
+    policy: &RetentionPolicy,
+    now: i64,
+) -> Result<u64, Error> {
+    if policy.is_unlimited() {
+        return Ok(0);
+    }
+
+    let not_exempt = policy.not_exempt_condition();
+    let mut deleted: u64 = 0;
+
+    // empty the clips in the trash longer than the limit,
+    // the exempt labels do not apply as the user deleted these clips
+    if policy.trash_auto_empty_days > 0 {
+        let oldest_deleted_at = now - (policy.trash_auto_empty_days as i64) * 24 * 60 * 60;
+        match connection.execute(
+            "DELETE FROM clips WHERE deleted_at IS NOT NULL AND deleted_at < ?",
+            [oldest_deleted_at],
+        ) {
+            Ok(res) => deleted += res as u64,
+            Err(err) => return Err(Error::DeleteClipFromDatabaseErr(0, err.to_string())),
+        };
+    }
+
+    // delete the clips older than the max age
+    if policy.max_clip_age_days > 0 {
+        let oldest_timestamp = now - (policy.max_clip_age_days as i64) * 24 * 60 * 60;
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&oldest_timestamp];
+        for label in policy.exempt_labels.iter() {
+            params.push(label);
+        }
+        match connection.execute(
+            &format!("DELETE FROM clips WHERE timestamp < ? AND {}", not_exempt),
+            params.as_slice(),
+        ) {
+            Ok(res) => deleted += res as u64,
+            Err(err) => return Err(Error::DeleteClipFromDatabaseErr(0, err.to_string())),
+        };
+    }
+
+    // delete the oldest clips more than the max count
+    if policy.max_clip_count > 0 {
+        let mut params: Vec<&dyn rusqlite::ToSql> = Vec::new();
+        for label in policy.exempt_labels.iter() {
+            params.push(label);
+        }
+        let max_clip_count = policy.max_clip_count as i64;
+        params.push(&max_clip_count);
+        match connection.execute(
+            &format!(
+                "DELETE FROM clips WHERE id IN (
+                    SELECT id FROM clips WHERE {}
+                    ORDER BY id ASC
+                    LIMIT max((SELECT COUNT(1) FROM clips) - ?, 0)
+                )",
+                not_exempt
+            ),
+            params.as_slice(),
+        ) {
+            Ok(res) => deleted += res as u64,
+            Err(err) => return Err(Error::DeleteClipFromDatabaseErr(0, err.to_string())),
+        };
+    }
+
+    // delete the oldest clips until the total text size is under the limit
+    if policy.max_total_text_bytes > 0 {
+        let total: i64 =
+            match connection.query_row("SELECT COALESCE(SUM(size), 0) FROM clips", [], |row| {
+                row.get(0)
+            }) {
+                Ok(total) => total,
+                Err(err) => return Err(Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+            };
+        let excess = total - policy.max_total_text_bytes as i64;
+        if excess > 0 {
+            let mut params: Vec<&dyn rusqlite::ToSql> = Vec::new();
+            for label in policy.exempt_labels.iter() {
+                params.push(label);
+            }
+            params.push(&excess);
+            // a clip is deleted if the clips older than it are not enough to cover the excess
+            match connection.execute(
+                &format!(
+                    "DELETE FROM clips WHERE id IN (
+                        SELECT id FROM (
+                            SELECT id,
+                                size,
+                                SUM(size) OVER (ORDER BY id ASC) AS running
+                            FROM clips WHERE {}
+                        ) WHERE running - size < ?
+                    )",
+                    not_exempt
+                ),
+                params.as_slice(),
+            ) {
+                Ok(res) => deleted += res as u64,
+                Err(err) => return Err(Error::DeleteClipFromDatabaseErr(0, err.to_string())),
+            };
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// apply the retention policy in the config to the database
+///
+/// trigger a tray update event if any clip is deleted
+///
+/// return the number of clips deleted
+pub async fn prune(app: &AppHandle) -> Result<u64, Error> {
+    let config = app.state::<ConfigMutex>();
+    let policy = RetentionPolicy::from_config(&*config.config.lock().await);
+    if policy.is_unlimited() {
+        return Ok(0);
+    }
+
+    let clip_state = app.state::<ClipStateMutex>();
+    let mut clip_state = clip_state.clip_state.lock().await;
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let mut db_connection = db_connection.database_connection.lock().await;
+    let transaction = match db_connection.transaction() {
+        Ok(transaction) => transaction,
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+    let deleted = prune_clips(&transaction, &policy, get_system_timestamp())?;
+    if let Err(err) = transaction.commit() {
+        return Err(Error::DatabaseWriteErr(err.to_string()));
+    }
+    drop(db_connection);
+
+    if deleted > 0 {
+        debug!("{} clips pruned by the retention policy", deleted);
+        // the current clip may be deleted
+        if let Some(id) = clip_state.current_clip {
+            if clip_state.get_clip(app, Some(id)).await?.is_none() {
+                clip_state.current_clip = clip_state.get_latest_clip_id(app).await?;
+            }
+        }
+        clip_state.trigger_tray_update_event(app).await;
+    }
+
+    Ok(deleted)
+}
+
+/// start a background thread which applies the retention policy periodically
+pub fn start_prune_timer(app: &AppHandle) {
+    let app = app.app_handle();
+    let res = std::thread::Builder::new()
+        .name("prune_clips".to_string())
+        .spawn(move || loop {
+            event_sender(&app, CopyClipEvent::PruneClipsEvent);
+            std::thread::sleep(std::time::Duration::from_secs(PRUNE_INTERVAL));
+        });
+    if let Err(err) = res {
+        warn!("failed to start the prune timer, error: {}", err);
+    }
+}
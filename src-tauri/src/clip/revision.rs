@@ -0,0 +1,304 @@
+use clip::ClipType;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    config::ConfigMutex,
+    database::{clip_hash, index_compressed_text, DatabaseStateMutex},
+    error,
+    event::{CopyClipEvent, EventSender},
+};
+
+use super::{
+    clip_data::ClipStateMutex,
+    get_system_timestamp,
+    text::{full_text, StoredText},
+};
+
+/// a previous text of an edited clip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipRevision {
+    /// the id of the revision
+    pub id: u64,
+    /// the id of the clip
+    pub clip_id: u64,
+    pub text: String,
+    /// when the text is replaced, unix timestamp in seconds
+    pub timestamp: i64,
+}
+
+/// replace the text of a clip, and keep the current text as a revision
+///   - the hash is updated, so the duplicate detection uses the new text
+///   - the full text search index is updated by the trigger
+///   - the clips in the trash and the image clips can not be edited,
+///     the text of an image clip is only a description, and its hash is the hash of the png
+///   - the html and rtf payloads are kept, only the plain text is replaced
+///   - the new text is compressed if it is larger than the compress threshold,
+///     the revisions are never compressed
+///
+/// return false if the text is not changed
+#[warn(unused_must_use)]
+pub fn set_clip_text(
+    connection: &Connection,
+    id: u64,
+    text: &str,
+    compress_threshold: u64,
+) -> Result<bool, error::Error> {
+    let transaction = match connection.unchecked_transaction() {
+        Ok(transaction) => transaction,
+        Err(err) => return Err(error::Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    let (current_text, compressed_text, clip_type): (String, Option<Vec<u8>>, u8) =
+        match transaction.query_row(
+            "SELECT text, compressed_text, type FROM clips WHERE id = ? AND deleted_at IS NULL",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ) {
+            Ok(res) => res,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(error::Error::ClipNotFoundErr(id as i64))
+            }
+            Err(err) => {
+                return Err(error::Error::GetClipDataFromDatabaseErr(
+                    id,
+                    err.to_string(),
+                ))
+            }
+        };
+    if ClipType::from(clip_type) == ClipType::Image {
+        return Err(error::Error::UpdateClipsInDatabaseErr(
+            id.to_string(),
+            "the image clips can not be edited".to_string(),
+        ));
+    }
+    let current_text = full_text(id, current_text, compressed_text)?;
+    if current_text == text {
+        return Ok(false);
+    }
+
+    match transaction.execute(
+        "INSERT INTO clip_revisions (clip_id, text, timestamp) VALUES (?, ?, ?)",
+        rusqlite::params![id, current_text, get_system_timestamp()],
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(error::Error::DatabaseWriteErr(err.to_string())),
+    };
+    let stored_text = StoredText::new(text, compress_threshold)?;
+    match transaction.execute(
+        "UPDATE clips SET text = ?, hash = ?, preview = ?, size = ?, compressed_text = ?
+        WHERE id = ?",
+        rusqlite::params![
+            stored_text.text,
+            clip_hash(text),
+            stored_text.preview,
+            stored_text.size,
+            stored_text.compressed_text,
+            id
+        ],
+    ) {
+        Ok(_) => (),
+        Err(err) => {
+            return Err(error::Error::UpdateClipsInDatabaseErr(
+                id.to_string(),
+                err.to_string(),
+            ))
+        }
+    };
+
+    if stored_text.compressed_text.is_some() {
+        index_compressed_text(&transaction, id, text)?;
+    }
+
+    match transaction.commit() {
+        Ok(_) => Ok(true),
+        Err(err) => Err(error::Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// get the revisions of a clip, the latest first
+pub fn list_revisions(
+    connection: &Connection,
+    clip_id: u64,
+) -> Result<Vec<ClipRevision>, error::Error> {
+    let mut stmt = match connection.prepare(
+        "SELECT id, clip_id, text, timestamp FROM clip_revisions
+        WHERE clip_id = ?
+        ORDER BY id DESC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                clip_id,
+                err.to_string(),
+            ))
+        }
+    };
+
+    let res = match stmt.query_map([clip_id], |row| {
+        Ok(ClipRevision {
+            id: row.get(0)?,
+            clip_id: row.get(1)?,
+            text: row.get(2)?,
+            timestamp: row.get(3)?,
+        })
+    }) {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                clip_id,
+                err.to_string(),
+            ))
+        }
+    };
+
+    let mut revisions = Vec::new();
+    for revision in res {
+        match revision {
+            Ok(revision) => revisions.push(revision),
+            Err(err) => {
+                return Err(error::Error::GetClipDataFromDatabaseErr(
+                    clip_id,
+                    err.to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(revisions)
+}
+
+/// get a revision by its id
+pub fn get_revision(connection: &Connection, id: u64) -> Result<ClipRevision, error::Error> {
+    match connection.query_row(
+        "SELECT id, clip_id, text, timestamp FROM clip_revisions WHERE id = ?",
+        [id],
+        |row| {
+            Ok(ClipRevision {
+                id: row.get(0)?,
+                clip_id: row.get(1)?,
+                text: row.get(2)?,
+                timestamp: row.get(3)?,
+            })
+        },
+    ) {
+        Ok(revision) => Ok(revision),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Err(error::Error::ClipRevisionNotFoundErr(id)),
+        Err(err) => Err(error::Error::GetClipDataFromDatabaseErr(
+            id,
+            err.to_string(),
+        )),
+    }
+}
+
+/// change the text of a clip, the previous text is kept as a revision
+///
+/// input: {
+///     id: u64, the id of the clip
+///     text: String, the new text
+/// }
+#[tauri::command]
+pub async fn edit_clip(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    event_sender: tauri::State<'_, EventSender>,
+    id: u64,
+    text: String,
+) -> Result<(), error::Error> {
+    let clip_state = clip_state.clip_state.lock().await;
+
+    let config = app.state::<ConfigMutex>();
+    let config = config.config.lock().await;
+    let compress_threshold = config.compress_threshold;
+    drop(config);
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let changed = set_clip_text(&db_connection, id, &text, compress_threshold)?;
+    drop(db_connection);
+
+    if !changed {
+        return Ok(());
+    }
+
+    clip_state.trigger_tray_update_event(&app).await;
+    drop(clip_state);
+
+    event_sender
+        .send(CopyClipEvent::SendNotificationEvent(
+            "Clip edited.".to_string(),
+        ))
+        .await;
+
+    Ok(())
+}
+
+/// get the previous texts of a clip
+///
+/// input: {
+///     id: u64, the id of the clip
+/// }
+///
+/// output: Vec<{
+///     id: u64, the id of the revision
+///     clip_id: u64,
+///     text: String,
+///     timestamp: i64, when the text is replaced
+/// }>, the latest first
+#[tauri::command]
+pub async fn get_clip_revisions(
+    app: AppHandle,
+    id: u64,
+) -> Result<Vec<ClipRevision>, error::Error> {
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    list_revisions(&db_connection, id)
+}
+
+/// change the text of a clip back to one of its revisions,
+/// the current text is kept as a new revision, so the restore can be reverted too
+///
+/// input: {
+///     id: u64, the id of the revision
+/// }
+#[tauri::command]
+pub async fn restore_clip_revision(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    event_sender: tauri::State<'_, EventSender>,
+    id: u64,
+) -> Result<(), error::Error> {
+    let clip_state = clip_state.clip_state.lock().await;
+
+    let config = app.state::<ConfigMutex>();
+    let config = config.config.lock().await;
+    let compress_threshold = config.compress_threshold;
+    drop(config);
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let revision = get_revision(&db_connection, id)?;
+    let changed = set_clip_text(
+        &db_connection,
+        revision.clip_id,
+        &revision.text,
+        compress_threshold,
+    )?;
+    drop(db_connection);
+
+    if !changed {
+        return Ok(());
+    }
+
+    clip_state.trigger_tray_update_event(&app).await;
+    drop(clip_state);
+
+    event_sender
+        .send(CopyClipEvent::SendNotificationEvent(
+            "Clip restored to the revision.".to_string(),
+        ))
+        .await;
+
+    Ok(())
+}
@@ -1,19 +1,27 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
 use log::debug;
-use rusqlite::Row;
+use rusqlite::{types::Value, Row};
 
 use tauri::{AppHandle, Manager};
 
-use crate::{
-    config::ConfigMutex,
-    database::{label_name_to_table_name, DatabaseStateMutex},
-    error,
-};
+use crate::{config::ConfigMutex, database::DatabaseStateMutex, error};
+
+use clip::{Clip, LabelFilter, SearchPage};
 
-use clip::Clip;
+use super::{
+    clip_data::ClipStateMutex,
+    order::{SearchOrder, SearchOrdering},
+    query::SearchQuery,
+};
 
-use super::clip_data::ClipStateMutex;
+/// the columns selected by the searches, the full text is not loaded
+///
+/// the normal, fuzzy and regexp searches match the text column,
+/// the full text of the clips and the preview of the compressed clips,
+/// so a search never decompresses a clip,
+/// the full text search matches the whole text of the compressed clips with the clips_fts index
+const CLIP_COLUMNS: &str = "clips.id, clips.type, clips.preview, clips.timestamp, clips.size";
 
 /// search for a clip in the database
 /// SELECT * FROM clips WHERE content MATCH 'linux';
@@ -22,337 +30,398 @@ use super::clip_data::ClipStateMutex;
 /// this function will not test if the row is valid
 ///
 /// as the row will not contain info about the labels, the clip will not have any label
+/// the text of the clip is the preview
 #[warn(unused_must_use)]
 fn clip_from_row(row: &Row) -> Result<Clip, rusqlite::Error> {
     let id = row.get("id")?;
-    let text = row.get("text")?;
+    let text = row.get("preview")?;
+    let size = row.get("size")?;
     let timestamp: i64 = row.get("timestamp")?;
     let clip_type: u8 = row.get("type")?;
 
     let clip = Clip {
         id,
         text: Arc::new(text),
+        size,
         timestamp,
         clip_type: clip_type.into(),
         labels: vec![],
+        representations: vec![],
     };
 
     Ok(clip)
 }
 
-/// normal search for a clip in the database
-/// SELECT count(*) FROM clips WHERE content LIKE '%linux%'
-/// will search for substring
+/// how a search method matches the text of the query
+struct Matcher<'a> {
+    /// the tables to select from, the clips table is named clips
+    from: &'a str,
+    /// the condition matching the text of the query, and the value to bind to it
+    condition: Option<(&'a str, Value)>,
+    /// the score of a clip for `SearchOrder::Score`, the higher the better,
+    /// and the value to bind to it
+    score: Option<(&'a str, Vec<Value>)>,
+}
+
+/// search a page of the clips matching the query with one sql query,
+/// the filters of the query are always applied, and the text of the query is matched by the matcher
 ///
-/// this will try select clips match data clip and min_id <= id <= max_id and maximum limit clips
-/// will return a list of clips
-pub async fn normal_search(
+/// this will try select clips match the query and min_id <= id <= max_id and maximum limit clips,
+/// ordered by the ordering and starting after its cursor
+async fn query_search(
     app: &AppHandle,
-    min_id: u64,
-    max_id: u64,
+    matcher: Matcher<'_>,
+    (min_id, max_id): (u64, u64),
     limit: u64,
-    data: String,
-    favourite: bool,
-    pinned: bool,
-) -> Result<HashMap<u64, Clip>, error::Error> {
-    let db_connection = app.state::<DatabaseStateMutex>();
-    let db_connection = db_connection.database_connection.lock().await;
-
-    let mut stmt: String =
-        "SELECT * FROM clips WHERE id BETWEEN ? AND ? AND text LIKE ?".to_string();
-
-    if favourite {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("favourite"),
-            label_name_to_table_name("favourite")
-        );
-    }
+    query: &SearchQuery,
+    ordering: &SearchOrdering,
+) -> Result<SearchPage, error::Error> {
+    // the score is selected first, and only bound if the clips are ordered by it
+    let (key_expr, mut values) = match (ordering.order, matcher.score) {
+        (SearchOrder::Score, Some((score, score_values))) => (score.to_string(), score_values),
+        _ => (ordering.key_expr().to_string(), Vec::new()),
+    };
 
-    if pinned {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("pinned"),
-            label_name_to_table_name("pinned")
-        );
+    let mut conditions = vec!["clips.deleted_at IS NULL".to_string()];
+    if let Some((condition, value)) = matcher.condition {
+        conditions.push(condition.to_string());
+        values.push(value);
     }
+    conditions.push("clips.id BETWEEN ? AND ?".to_string());
+    values.push(Value::Integer(min_id as i64));
+    values.push(Value::Integer(max_id.min(i64::MAX as u64) as i64));
+
+    let (filter_conditions, filter_values) = query.filter_conditions();
+    conditions.extend(filter_conditions);
+    values.extend(filter_values);
+
+    let cursor_condition = match ordering.cursor_condition() {
+        Some((condition, cursor_values)) => {
+            values.extend(cursor_values);
+            condition
+        }
+        None => "1".to_string(),
+    };
+    values.push(Value::Integer(limit as i64));
 
     let stmt = format!(
-        "{}
-        ORDER BY id DESC LIMIT ?",
-        stmt
+        "SELECT * FROM (
+            SELECT {}, {} AS sort_key FROM {}
+            WHERE {}
+        )
+        WHERE {}
+        ORDER BY {} LIMIT ?",
+        CLIP_COLUMNS,
+        key_expr,
+        matcher.from,
+        conditions.join(" AND "),
+        cursor_condition,
+        ordering.order_by()
     );
 
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+
     let mut stmt = match db_connection.prepare(&stmt) {
         Ok(stmt) => stmt,
         Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
     };
 
-    let data = format!("%{}%", data);
-
-    let res = match stmt.query_map(
-        [
-            min_id.to_string(),
-            max_id.to_string(),
-            data,
-            limit.to_string(),
-        ],
-        clip_from_row,
-    ) {
+    let res = match stmt.query_map(rusqlite::params_from_iter(values), |row| {
+        let sort_key: Value = row.get("sort_key")?;
+        Ok((clip_from_row(row)?, sort_key))
+    }) {
         Ok(res) => res,
         Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
     };
 
-    let mut clips = HashMap::new();
+    let mut clips = Vec::new();
+    let mut last_key = None;
     for clip in res {
-        let clip = match clip {
+        let (clip, sort_key) = match clip {
             Ok(clip) => clip,
             Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
         };
-        clips.insert(clip.id, clip);
+        last_key = Some((sort_key, clip.id));
+        clips.push(clip);
     }
 
-    Ok(clips)
+    // a full page may not be the last one
+    let cursor = match last_key {
+        Some((sort_key, id)) if clips.len() as u64 >= limit => ordering
+            .cursor_after(sort_key, id)
+            .map(|cursor| cursor.encode()),
+        _ => None,
+    };
+
+    Ok(SearchPage { clips, cursor })
+}
+
+/// normal search for a clip in the database
+/// SELECT count(*) FROM clips WHERE content LIKE '%linux%'
+/// will search for substring
+///
+/// this will try select clips match the query and min_id <= id <= max_id and maximum limit clips
+/// will return a page of clips
+pub async fn normal_search(
+    app: &AppHandle,
+    id_range: (u64, u64),
+    limit: u64,
+    query: &SearchQuery,
+    ordering: &SearchOrdering,
+) -> Result<SearchPage, error::Error> {
+    let matcher = Matcher {
+        from: "clips",
+        condition: Some((
+            "clips.text LIKE ?",
+            Value::Text(format!("%{}%", query.text)),
+        )),
+        score: None,
+    };
+
+    query_search(app, matcher, id_range, limit, query, ordering).await
 }
 
 /// fuzzy search for a clip in the database
 /// try iterate with each clip in the database such that the id is between min_id and max_id and maximum limit clips
-/// will return a list of clips
+/// will return a page of clips
 ///
-/// this will try select clips match data clip and min_id <= id <= max_id and maximum limit clips
+/// this will try select clips match the query and min_id <= id <= max_id and maximum limit clips
 pub async fn fuzzy_search(
     app: &AppHandle,
-    min_id: u64,
-    max_id: u64,
+    id_range: (u64, u64),
     limit: u64,
-    data: String,
-    favourite: bool, // true: filter-on, false: filter-off
-    pinned: bool,
-) -> Result<HashMap<u64, Clip>, error::Error> {
-    let db_connection = app.state::<DatabaseStateMutex>();
-    let db_connection = db_connection.database_connection.lock().await;
-
-    let mut stmt: String =
-        "SELECT * FROM clips WHERE id BETWEEN ? AND ? AND fuzzy_search(text, ?) > 0".to_string();
-
-    if favourite {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("favourite"),
-            label_name_to_table_name("favourite")
-        );
-    }
-
-    if pinned {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("pinned"),
-            label_name_to_table_name("pinned")
-        );
-    }
-
-    let stmt = format!(
-        "{}
-        ORDER BY id DESC LIMIT ?",
-        stmt
-    );
-
-    let mut stmt = match db_connection.prepare(&stmt) {
-        Ok(stmt) => stmt,
-        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+    query: &SearchQuery,
+    ordering: &SearchOrdering,
+) -> Result<SearchPage, error::Error> {
+    let matcher = Matcher {
+        from: "clips",
+        condition: Some((
+            "fuzzy_search(?, clips.text) > 0",
+            Value::Text(query.text.clone()),
+        )),
+        score: Some((
+            "fuzzy_search(?, clips.text)",
+            vec![Value::Text(query.text.clone())],
+        )),
     };
 
-    let res = match stmt.query_map(
-        [
-            min_id.to_string(),
-            max_id.to_string(),
-            data,
-            limit.to_string(),
-        ],
-        clip_from_row,
-    ) {
-        Ok(res) => res,
-        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
-    };
-
-    let mut clips = HashMap::new();
-    for clip in res {
-        let clip = match clip {
-            Ok(clip) => clip,
-            Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
-        };
-        clips.insert(clip.id, clip);
-    }
-
-    Ok(clips)
+    query_search(app, matcher, id_range, limit, query, ordering).await
 }
 
 /// regexp search for a clip in the database
 /// try iterate with each clip in the database such that the id is between min_id and max_id and maximum limit clips
-/// will return a list of clips
+/// will return a page of clips
 ///
-/// this will try select clips match data clip and min_id <= id <= max_id and maximum limit clips
+/// this will try select clips match the query and min_id <= id <= max_id and maximum limit clips
 pub async fn regexp_search(
     app: &AppHandle,
-    min_id: u64,
-    max_id: u64,
+    id_range: (u64, u64),
     limit: u64,
-    data: String,
-    favourite: bool, // true: filter-on, false: filter-off
-    pinned: bool,
-) -> Result<HashMap<u64, Clip>, error::Error> {
-    let db_connection = app.state::<DatabaseStateMutex>();
-    let db_connection = db_connection.database_connection.lock().await;
+    query: &SearchQuery,
+    ordering: &SearchOrdering,
+) -> Result<SearchPage, error::Error> {
+    let matcher = Matcher {
+        from: "clips",
+        condition: Some(("regexp(?, clips.text)", Value::Text(query.text.clone()))),
+        score: None,
+    };
 
-    let mut stmt: String =
-        "SELECT * FROM clips WHERE id BETWEEN ? AND ? AND regexp(text, ?)".to_string();
-
-    if favourite {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("favourite"),
-            label_name_to_table_name("favourite")
-        );
-    }
+    query_search(app, matcher, id_range, limit, query, ordering).await
+}
 
-    if pinned {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("pinned"),
-            label_name_to_table_name("pinned")
-        );
+/// convert the user input to a fts5 query
+///
+/// - the text inside double quotes is a phrase, `"hello world"`
+/// - a word or phrase ending with `*` is a prefix query, `hel*`
+/// - all the words and phrases must be matched
+///
+/// every word is quoted, so the fts5 operators and special characters in the input
+/// are treated as plain text, and the query is always valid
+///
+/// return None if there is nothing to search
+fn to_fts5_query(data: &str) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let mut term = String::new();
+        if c == '"' {
+            // phrase, until the closing quote or the end of the input
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                term.push(c);
+            }
+        } else {
+            term.push(c);
+            while let Some(c) = chars.peek() {
+                if c.is_whitespace() || *c == '"' {
+                    break;
+                }
+                term.push(*c);
+                chars.next();
+            }
+        }
+
+        let mut prefix = false;
+        if term.ends_with('*') {
+            term = term.trim_end_matches('*').to_string();
+            prefix = true;
+        } else if chars.peek() == Some(&'*') {
+            chars.next();
+            prefix = true;
+        }
+        if term.trim().is_empty() {
+            continue;
+        }
+
+        let term = format!("\"{}\"", term.replace('"', "\"\""));
+        if prefix {
+            terms.push(format!("{}*", term));
+        } else {
+            terms.push(term);
+        }
     }
 
-    let stmt = format!(
-        "{}
-        ORDER BY id DESC LIMIT ?",
-        stmt
-    );
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
 
-    let mut stmt = match db_connection.prepare(&stmt) {
-        Ok(stmt) => stmt,
-        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+/// full text search for a clip in the database, using the clips_fts fts5 table
+///
+/// this will try select clips match the query and min_id <= id <= max_id and maximum limit clips,
+/// the score of a clip is its bm25 relevance
+pub async fn fulltext_search(
+    app: &AppHandle,
+    id_range: (u64, u64),
+    limit: u64,
+    query: &SearchQuery,
+    ordering: &SearchOrdering,
+) -> Result<SearchPage, error::Error> {
+    let fts5_query = match to_fts5_query(&query.text) {
+        Some(fts5_query) => fts5_query,
+        None => return Ok(SearchPage::default()),
     };
 
-    let res = match stmt.query_map(
-        [
-            min_id.to_string(),
-            max_id.to_string(),
-            data,
-            limit.to_string(),
-        ],
-        clip_from_row,
-    ) {
-        Ok(res) => res,
-        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+    // the smaller bm25 is the more relevant
+    let matcher = Matcher {
+        from: "clips_fts INNER JOIN clips ON clips.id = clips_fts.rowid",
+        condition: Some(("clips_fts MATCH ?", Value::Text(fts5_query))),
+        score: Some(("-bm25(clips_fts)", Vec::new())),
     };
 
-    let mut clips = HashMap::new();
-    for clip in res {
-        let clip = match clip {
-            Ok(clip) => clip,
-            Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
-        };
-        clips.insert(clip.id, clip);
-    }
-
-    Ok(clips)
+    query_search(app, matcher, id_range, limit, query, ordering).await
 }
 
-/// Return all the clips in the database with id, min_id <= id <= max_id.
+/// Return a page of the clips in the database matching the filters of the query, with id, min_id <= id <= max_id.
 /// The total number of clips is limited to limit.
 async fn empty_search(
     app: &AppHandle,
-    min_id: u64,
-    max_id: u64,
+    id_range: (u64, u64),
     limit: u64,
-    favourite: bool, // true: filter-on, false: filter-off
-    pinned: bool,
-) -> Result<HashMap<u64, Clip>, error::Error> {
-    let db_connection = app.state::<DatabaseStateMutex>();
-    let db_connection = db_connection.database_connection.lock().await;
-
-    let mut stmt: String = "SELECT * FROM clips WHERE id BETWEEN ? AND ?".to_string();
-
-    if favourite {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("favourite"),
-            label_name_to_table_name("favourite")
-        );
-    }
+    query: &SearchQuery,
+    ordering: &SearchOrdering,
+) -> Result<SearchPage, error::Error> {
+    let matcher = Matcher {
+        from: "clips",
+        condition: None,
+        score: None,
+    };
 
-    if pinned {
-        stmt = format!(
-            "{}
-            INNER JOIN {}
-            ON clips.id = {}.id;
-            ",
-            stmt,
-            label_name_to_table_name("pinned"),
-            label_name_to_table_name("pinned")
-        );
-    }
+    query_search(app, matcher, id_range, limit, query, ordering).await
+}
 
-    let stmt = format!(
-        "{}
-        ORDER BY id DESC LIMIT ?",
-        stmt
-    );
+/// find the clips with the same text as the clip with the id,
+/// the clip itself is not included
+///
+/// the clips are found with the indexed hash, ordered by id descending,
+/// the size and the text column are compared in case of a hash collision,
+/// the text column is the preview of the compressed clips, so no clip is decompressed
+pub async fn duplicate_search(app: &AppHandle, id: u64) -> Result<Vec<Clip>, error::Error> {
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
 
-    let mut stmt = match db_connection.prepare(&stmt) {
+    let mut stmt = match db_connection.prepare(&format!(
+        "SELECT {} FROM clips
+        INNER JOIN clips AS origin ON origin.id = ?
+        WHERE clips.hash = origin.hash AND clips.id != origin.id
+            AND clips.size = origin.size AND clips.text = origin.text
+            AND clips.deleted_at IS NULL
+        ORDER BY clips.id DESC",
+        CLIP_COLUMNS
+    )) {
         Ok(stmt) => stmt,
-        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                id,
+                err.to_string(),
+            ))
+        }
     };
 
-    let res = match stmt.query_map(
-        [min_id.to_string(), max_id.to_string(), limit.to_string()],
-        clip_from_row,
-    ) {
+    let res = match stmt.query_map([id], clip_from_row) {
         Ok(res) => res,
-        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                id,
+                err.to_string(),
+            ))
+        }
     };
 
-    let mut clips = HashMap::new();
+    let mut clips = Vec::new();
     for clip in res {
         let clip = match clip {
             Ok(clip) => clip,
-            Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+            Err(err) => {
+                return Err(error::Error::GetClipDataFromDatabaseErr(
+                    id,
+                    err.to_string(),
+                ))
+            }
         };
-        clips.insert(clip.id, clip);
+        clips.push(clip);
     }
 
     Ok(clips)
 }
 
+/// find the duplicates of a clip, the clips with the same text
+///
+/// input {
+///     id: u64, the id of the clip
+/// }
+///
+/// output {
+///     Vec<Clip>, ordered by id descending, the clip itself is not included
+/// }
+#[tauri::command]
+pub async fn search_duplicate_clips(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    id: u64,
+) -> Result<Vec<Clip>, error::Error> {
+    let mut res = duplicate_search(&app, id).await?;
+
+    let clip_state = clip_state.clip_state.lock().await;
+    for clip in res.iter_mut() {
+        clip.labels = clip_state
+            .get_clip_labels(&app, clip.id)
+            .await?
+            .unwrap_or_default();
+    }
+
+    Ok(res)
+}
+
 /// get the max id of the clip in the database,
 /// if no clip in the database, return 0
 #[tauri::command]
@@ -369,98 +438,100 @@ pub async fn get_max_id(
     Ok(res.unwrap())
 }
 
-/// search for a clip in the database
+/// search for a page of clips in the database
 ///
 /// the method is decide by the input
-/// the limit is the config.search_clip_per_page
+/// the limit is the config.search_clip_per_batch
+///
+/// the data is parsed as a `SearchQuery`, its filters are applied for every method,
+/// and its text is matched by the method
+///
+/// the clips are ordered by the order in the direction across the whole history,
+/// the clips with the same order key are ordered by id,
+/// pass the cursor of a page to get the next page with the same arguments
 ///
 /// input {
 ///     data: String,
-///     min_id: i64,
-///     max_id: i64,
-///     search_method: String,
+///     minid: i64, the min id of the clips, negative for no limit
+///     maxid: i64, the max id of the clips, negative for no limit
+///     searchmethod: String, one of fuzzy, normal, regexp and fulltext
+///     labels: Option<LabelFilter>, the clips should match the label filter besides the labels in the data
+///     order: Option<String>, one of id, time, size, text and score, default to id,
+///         the score is the fuzzy score for fuzzy, the relevance for fulltext, and the id for the other methods
+///     direction: Option<String>, asc or desc, default to desc
+///     cursor: Option<String>, the cursor of the previous page, None for the first page
 /// }
 ///
 /// output {
-///     HashMap<id, Clip>
+///     clips: Vec<Clip>,
+///     cursor: Option<String>, the cursor of the next page, None if this is the last page
 /// }
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn search_clips(
     app: AppHandle,
     data: String,
     minid: i64,
-    maxid: u64,
-    favourite: bool, // true: filter-on, false: filter-off
-    pinned: bool,
+    maxid: i64,
+    labels: Option<LabelFilter>,
     searchmethod: String,
-) -> Result<HashMap<u64, Clip>, String> {
+    order: Option<String>,
+    direction: Option<String>,
+    cursor: Option<String>,
+) -> Result<SearchPage, String> {
     debug!(
-        "search_clips: data: {}, minid: {}, maxid: {}, searchmethod: {}",
-        data, minid, maxid, searchmethod
+        "search_clips: data: {}, minid: {}, maxid: {}, searchmethod: {}, order: {:?}, direction: {:?}",
+        data, minid, maxid, searchmethod, order, direction
     );
     let config = app.state::<ConfigMutex>();
     let config = config.config.lock().await;
     let limit = config.search_clip_per_batch;
     drop(config);
     let clip_state = app.state::<ClipStateMutex>();
-    let minid = if minid < 0 {
-        match clip_state
-            .clip_state
-            .lock()
-            .await
-            .get_latest_clip_id(&app)
-            .await
-        {
-            Ok(res) => res.unwrap_or(0),
-            Err(err) => {
-                return Err(err.message());
-            }
-        }
-    } else {
-        minid as u64
+    let id_range = (
+        if minid < 0 { 0 } else { minid as u64 },
+        if maxid < 0 { u64::MAX } else { maxid as u64 },
+    );
+
+    let mut query = match SearchQuery::parse(&data) {
+        Ok(query) => query,
+        Err(err) => return Err(err.message()),
     };
+    if let Some(labels) = labels {
+        if !labels.is_empty() {
+            query.label_filters.push(labels);
+        }
+    }
+    let ordering =
+        match SearchOrdering::parse(order.as_deref(), direction.as_deref(), cursor.as_deref()) {
+            Ok(ordering) => ordering,
+            Err(err) => return Err(err.message()),
+        };
 
-    // if data is empty, return all clips
-    let mut res = if data.is_empty() {
+    // if there is no text to match, return all clips matching the filters
+    let res = if query.text.is_empty() {
         debug!("search_clips: empty search");
-        let res = empty_search(&app, minid, maxid, limit, favourite, pinned).await;
-        if let Err(err) = res {
-            return Err(err.message());
-        }
-        res.unwrap()
+        empty_search(&app, id_range, limit, &query, &ordering).await
     } else {
         match searchmethod.as_str() {
-            "fuzzy" => {
-                let res = fuzzy_search(&app, minid, maxid, limit, data, favourite, pinned).await;
-                if let Err(err) = res {
-                    return Err(err.message());
-                }
-                res.unwrap()
-            }
-            "normal" => {
-                let res = normal_search(&app, minid, maxid, limit, data, favourite, pinned).await;
-                if let Err(err) = res {
-                    return Err(err.message());
-                }
-                res.unwrap()
-            }
-            "regexp" => {
-                let res = regexp_search(&app, minid, maxid, limit, data, favourite, pinned).await;
-                if let Err(err) = res {
-                    return Err(err.message());
-                }
-                res.unwrap()
-            }
+            "fuzzy" => fuzzy_search(&app, id_range, limit, &query, &ordering).await,
+            "normal" => normal_search(&app, id_range, limit, &query, &ordering).await,
+            "regexp" => regexp_search(&app, id_range, limit, &query, &ordering).await,
+            "fulltext" => fulltext_search(&app, id_range, limit, &query, &ordering).await,
             _ => return Err("invalid search method".to_string()),
         }
     };
+    let mut res = match res {
+        Ok(res) => res,
+        Err(err) => return Err(err.message()),
+    };
 
-    for (id, clip) in res.iter_mut() {
+    for clip in res.clips.iter_mut() {
         clip.labels = match clip_state
             .clip_state
             .lock()
             .await
-            .get_clip_labels(&app, *id)
+            .get_clip_labels(&app, clip.id)
             .await
         {
             Ok(labels) => labels.unwrap_or_default(),
@@ -472,3 +543,54 @@ pub async fn search_clips(
 
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_fts5_query_empty() {
+        assert_eq!(to_fts5_query(""), None);
+        assert_eq!(to_fts5_query("   "), None);
+        assert_eq!(to_fts5_query(r#""" * "  ""#), None);
+    }
+
+    #[test]
+    fn to_fts5_query_words_and_phrases() {
+        assert_eq!(
+            to_fts5_query(r#"hello "big world""#),
+            Some(r#""hello" "big world""#.to_string())
+        );
+    }
+
+    #[test]
+    fn to_fts5_query_prefix() {
+        assert_eq!(to_fts5_query("hel*"), Some(r#""hel"*"#.to_string()));
+        assert_eq!(
+            to_fts5_query(r#""hello wor"*"#),
+            Some(r#""hello wor"*"#.to_string())
+        );
+    }
+
+    #[test]
+    fn to_fts5_query_unterminated_quote() {
+        assert_eq!(
+            to_fts5_query(r#"foo "hello wor"#),
+            Some(r#""foo" "hello wor""#.to_string())
+        );
+    }
+
+    #[test]
+    fn to_fts5_query_escape_operators() {
+        assert_eq!(
+            to_fts5_query("a OR b NOT c AND d"),
+            Some(r#""a" "OR" "b" "NOT" "c" "AND" "d""#.to_string())
+        );
+        assert_eq!(
+            to_fts5_query("NEAR(a b) col:value -x ^y +z"),
+            Some(r#""NEAR(a" "b)" "col:value" "-x" "^y" "+z""#.to_string())
+        );
+        // a quote inside a word starts a phrase
+        assert_eq!(to_fts5_query(r#"a"b c"#), Some(r#""a" "b c""#.to_string()));
+    }
+}
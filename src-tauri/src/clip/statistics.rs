@@ -0,0 +1,255 @@
+use clip::ClipType;
+use rusqlite::{Connection, Params, Row};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{database::DatabaseStateMutex, error};
+
+use super::get_system_timestamp;
+
+/// the number of days counted in `clips_per_day`
+const STATISTICS_DAYS: i64 = 30;
+/// the number of weeks counted in `clips_per_week`
+const STATISTICS_WEEKS: i64 = 52;
+/// the max number of texts in `most_copied`
+const MOST_COPIED_LIMIT: u64 = 10;
+
+/// the statistics of the clips history, the clips in the trash are not counted
+/// except in `trash_clips`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipStatistics {
+    /// the number of clips
+    pub total_clips: u64,
+    /// the number of clips in the trash
+    pub trash_clips: u64,
+    /// the size of the text of all clips in bytes
+    pub total_size: u64,
+    /// the average size of the text of a clip in bytes
+    pub average_size: f64,
+    /// the size of the database file in bytes
+    pub database_size: u64,
+    /// the number of clips of each type
+    pub clips_per_type: Vec<ClipTypeCount>,
+    /// the number of clips created each day in the last `STATISTICS_DAYS` days,
+    /// the days without clips are skipped, the latest day first
+    pub clips_per_day: Vec<PeriodCount>,
+    /// the number of clips created each week in the last `STATISTICS_WEEKS` weeks,
+    /// the weeks without clips are skipped, the latest week first
+    pub clips_per_week: Vec<PeriodCount>,
+    /// the number of clips with each label, the labels without clips are included
+    pub clips_per_label: Vec<LabelCount>,
+    /// the texts copied more than once, the most copied first
+    pub most_copied: Vec<CopiedText>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipTypeCount {
+    pub clip_type: ClipType,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodCount {
+    /// the day as YYYY-MM-DD, or the ISO 8601 week as YYYY-Www, like 2024-W01, in the local time
+    pub period: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelCount {
+    pub label: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopiedText {
+    /// the preview of the text
+    pub text: String,
+    /// the number of times the text was copied,
+    /// including the duplicates deleted automatically
+    pub count: u64,
+    /// the id of the latest clip with the text
+    pub id: u64,
+    /// the timestamp of the latest clip with the text
+    pub timestamp: i64,
+}
+
+/// run the query and map every row
+fn collect_rows<T, P, F>(
+    connection: &Connection,
+    sql: &str,
+    params: P,
+    f: F,
+) -> Result<Vec<T>, error::Error>
+where
+    P: Params,
+    F: FnMut(&Row) -> Result<T, rusqlite::Error>,
+{
+    let mut stmt = match connection.prepare(sql) {
+        Ok(stmt) => stmt,
+        Err(err) => return Err(error::Error::GetClipStatisticsErr(err.to_string())),
+    };
+
+    let rows = match stmt.query_map(params, f) {
+        Ok(rows) => rows,
+        Err(err) => return Err(error::Error::GetClipStatisticsErr(err.to_string())),
+    };
+
+    let mut res = Vec::new();
+    for row in rows {
+        match row {
+            Ok(row) => res.push(row),
+            Err(err) => return Err(error::Error::GetClipStatisticsErr(err.to_string())),
+        }
+    }
+
+    Ok(res)
+}
+
+/// count the clips created in each period since the timestamp,
+/// the format is the strftime format of the period
+fn count_clips_per_period(
+    connection: &Connection,
+    format: &str,
+    since: i64,
+) -> Result<Vec<PeriodCount>, error::Error> {
+    collect_rows(
+        connection,
+        "SELECT strftime(?, timestamp, 'unixepoch', 'localtime') AS period, COUNT(*) FROM clips
+        WHERE deleted_at IS NULL AND timestamp >= ?
+        GROUP BY period
+        ORDER BY period DESC",
+        rusqlite::params![format, since],
+        |row| {
+            Ok(PeriodCount {
+                period: row.get(0)?,
+                count: row.get(1)?,
+            })
+        },
+    )
+}
+
+/// compute the statistics of the clips history with aggregate queries
+///
+/// `most_copied` sums the copy_count of the clips with the same text,
+/// so the copies are counted whether the duplicate clips are deleted automatically or kept
+pub fn get_statistics(connection: &Connection, now: i64) -> Result<ClipStatistics, error::Error> {
+    let (total_clips, total_size, average_size, trash_clips) = match connection.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(size), 0), COALESCE(AVG(size), 0),
+            (SELECT COUNT(*) FROM clips WHERE deleted_at IS NOT NULL)
+        FROM clips WHERE deleted_at IS NULL",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    ) {
+        Ok(res) => res,
+        Err(err) => return Err(error::Error::GetClipStatisticsErr(err.to_string())),
+    };
+
+    let database_size = match connection.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    ) {
+        Ok(res) => res,
+        Err(err) => return Err(error::Error::GetClipStatisticsErr(err.to_string())),
+    };
+
+    let clips_per_type = collect_rows(
+        connection,
+        "SELECT type, COUNT(*) FROM clips WHERE deleted_at IS NULL GROUP BY type ORDER BY type",
+        [],
+        |row| {
+            let clip_type: u8 = row.get(0)?;
+            Ok(ClipTypeCount {
+                clip_type: clip_type.into(),
+                count: row.get(1)?,
+            })
+        },
+    )?;
+
+    let clips_per_day =
+        count_clips_per_period(connection, "%Y-%m-%d", now - STATISTICS_DAYS * 24 * 60 * 60)?;
+    let clips_per_week = count_clips_per_period(
+        connection,
+        // the ISO year and week, supported by the bundled sqlite since 3.46,
+        // the first days of January can be in the last week of the previous year
+        "%G-W%V",
+        now - STATISTICS_WEEKS * 7 * 24 * 60 * 60,
+    )?;
+
+    let clips_per_label = collect_rows(
+        connection,
+        "SELECT labels.name, COUNT(clips.id) FROM labels
+        LEFT JOIN clip_labels ON clip_labels.label = labels.name
+        LEFT JOIN clips ON clips.id = clip_labels.clip_id AND clips.deleted_at IS NULL
+        GROUP BY labels.name
+        ORDER BY labels.name",
+        [],
+        |row| {
+            Ok(LabelCount {
+                label: row.get(0)?,
+                count: row.get(1)?,
+            })
+        },
+    )?;
+
+    // the preview and the id are taken from the row with the max timestamp
+    let most_copied = collect_rows(
+        connection,
+        "SELECT preview, SUM(copy_count) AS copies, id, MAX(timestamp) FROM clips
+        WHERE deleted_at IS NULL
+        GROUP BY hash
+        HAVING copies > 1
+        ORDER BY copies DESC, MAX(timestamp) DESC
+        LIMIT ?",
+        [MOST_COPIED_LIMIT],
+        |row| {
+            Ok(CopiedText {
+                text: row.get(0)?,
+                count: row.get(1)?,
+                id: row.get(2)?,
+                timestamp: row.get(3)?,
+            })
+        },
+    )?;
+
+    Ok(ClipStatistics {
+        total_clips,
+        trash_clips,
+        total_size,
+        average_size,
+        database_size,
+        clips_per_type,
+        clips_per_day,
+        clips_per_week,
+        clips_per_label,
+        most_copied,
+    })
+}
+
+/// get the statistics of the clips history
+///
+/// input: {}
+///
+/// output: {
+///     total_clips: u64,
+///     trash_clips: u64,
+///     total_size: u64, in bytes
+///     average_size: f64, in bytes
+///     database_size: u64, in bytes
+///     clips_per_type: Vec<{ clip_type: ClipType, count: u64 }>,
+///     clips_per_day: Vec<{ period: String, count: u64 }>, the last 30 days, the latest first
+///     clips_per_week: Vec<{ period: String, count: u64 }>, the last 52 ISO weeks as YYYY-Www, the latest first
+///     clips_per_label: Vec<{ label: String, count: u64 }>,
+///     most_copied: Vec<{ text: String, count: u64, id: u64, timestamp: i64 }>, the most copied first,
+///         count is the number of copies, including the duplicates deleted automatically
+/// }
+#[tauri::command]
+pub async fn get_clip_statistics(app: AppHandle) -> Result<ClipStatistics, error::Error> {
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let res = get_statistics(&db_connection, get_system_timestamp());
+    drop(db_connection);
+
+    res
+}
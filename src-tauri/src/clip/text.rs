@@ -0,0 +1,192 @@
+use std::io::{Read, Write};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager};
+
+use crate::{database::DatabaseStateMutex, error};
+
+/// the max number of chars kept in the preview column,
+/// the tray and the search list only show the preview
+pub const PREVIEW_MAX_CHARS: usize = 1024;
+
+/// the text of a clip as it is stored in the clips table
+#[derive(Debug, Clone)]
+pub struct StoredText {
+    /// the text column, the full text, or the preview if the text is compressed
+    pub text: String,
+    /// the first `PREVIEW_MAX_CHARS` chars of the text
+    pub preview: String,
+    /// the size of the full text in bytes
+    pub size: u64,
+    /// the full text compressed with zlib, None if the text is not compressed
+    pub compressed_text: Option<Vec<u8>>,
+}
+
+impl StoredText {
+    /// compress the text if it is larger than the threshold in bytes,
+    /// 0 means the text is never compressed
+    pub fn new(text: &str, compress_threshold: u64) -> Result<Self, error::Error> {
+        let preview = clip_preview(text);
+        let size = text.len() as u64;
+        if compress_threshold == 0 || size <= compress_threshold {
+            return Ok(Self {
+                text: text.to_string(),
+                preview,
+                size,
+                compressed_text: None,
+            });
+        }
+
+        Ok(Self {
+            text: preview.clone(),
+            preview,
+            size,
+            compressed_text: Some(compress_text(text)?),
+        })
+    }
+}
+
+/// the preview of a text, the first `PREVIEW_MAX_CHARS` chars
+pub fn clip_preview(text: &str) -> String {
+    text.chars().take(PREVIEW_MAX_CHARS).collect()
+}
+
+/// cut the text to at most max_bytes bytes, without splitting a char
+pub fn truncate_text(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// compress a text with zlib
+pub fn compress_text(text: &str) -> Result<Vec<u8>, error::Error> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    if let Err(err) = encoder.write_all(text.as_bytes()) {
+        return Err(error::Error::CompressClipTextErr(err.to_string()));
+    }
+    match encoder.finish() {
+        Ok(data) => Ok(data),
+        Err(err) => Err(error::Error::CompressClipTextErr(err.to_string())),
+    }
+}
+
+/// decompress a text compressed by `compress_text`,
+/// the id is only used in the error
+pub fn decompress_text(id: u64, data: &[u8]) -> Result<String, error::Error> {
+    let mut text = String::new();
+    match ZlibDecoder::new(data).read_to_string(&mut text) {
+        Ok(_) => Ok(text),
+        Err(err) => Err(error::Error::DecompressClipTextErr(id, err.to_string())),
+    }
+}
+
+/// the full text of a clip from its text and compressed_text columns
+pub fn full_text(
+    id: u64,
+    text: String,
+    compressed_text: Option<Vec<u8>>,
+) -> Result<String, error::Error> {
+    match compressed_text {
+        Some(data) => decompress_text(id, &data),
+        None => Ok(text),
+    }
+}
+
+/// get the full text of a clip, decompressed if needed,
+/// the clips in the trash are included
+///
+/// None if the clip is not found
+pub fn get_clip_text(connection: &Connection, id: u64) -> Result<Option<String>, error::Error> {
+    let (text, compressed_text): (String, Option<Vec<u8>>) = match connection.query_row(
+        "SELECT text, compressed_text FROM clips WHERE id = ?",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ) {
+        Ok(res) => res,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                id,
+                err.to_string(),
+            ))
+        }
+    };
+
+    Ok(Some(full_text(id, text, compressed_text)?))
+}
+
+/// get the full text of a clip, the search list only has the preview
+///
+/// input: {
+///     id: u64, the id of the clip
+/// }
+///
+/// output: String
+#[tauri::command]
+pub async fn get_full_clip_text(app: AppHandle, id: u64) -> Result<String, error::Error> {
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let text = get_clip_text(&db_connection, id)?;
+    drop(db_connection);
+
+    match text {
+        Some(text) => Ok(text),
+        None => Err(error::Error::ClipNotFoundErr(id as i64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_text_on_char_boundary() {
+        // é is 2 bytes, the cut inside it moves back
+        assert_eq!(truncate_text("héllo", 2), "h");
+        assert_eq!(truncate_text("héllo", 3), "hé");
+        assert_eq!(truncate_text("😀", 3), "");
+        assert_eq!(truncate_text("abc", 3), "abc");
+        assert_eq!(truncate_text("abc", 10), "abc");
+    }
+
+    #[test]
+    fn clip_preview_counts_chars() {
+        let text = "é".repeat(PREVIEW_MAX_CHARS + 1);
+        let preview = clip_preview(&text);
+        assert_eq!(preview.chars().count(), PREVIEW_MAX_CHARS);
+        assert_eq!(preview.len(), PREVIEW_MAX_CHARS * 2);
+    }
+
+    #[test]
+    fn stored_text_compressed_over_threshold() {
+        let text = "a".repeat(2000);
+
+        let stored = StoredText::new(&text, 0).unwrap();
+        assert_eq!(stored.text, text);
+        assert_eq!(stored.compressed_text, None);
+
+        let stored = StoredText::new(&text, 2000).unwrap();
+        assert_eq!(stored.compressed_text, None);
+
+        let stored = StoredText::new(&text, 1999).unwrap();
+        assert_eq!(stored.text, stored.preview);
+        assert_eq!(stored.preview.len(), PREVIEW_MAX_CHARS);
+        assert_eq!(stored.size, 2000);
+        assert_eq!(
+            full_text(0, stored.text, stored.compressed_text).unwrap(),
+            text
+        );
+    }
+
+    #[test]
+    fn decompress_invalid_data() {
+        assert!(decompress_text(1, b"not zlib").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use clip::Clip;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    database::DatabaseStateMutex,
+    error,
+    event::{CopyClipEvent, EventSender},
+};
+
+use super::clip_data::ClipStateMutex;
+
+/// a clip in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashClip {
+    #[serde(flatten)]
+    pub clip: Clip,
+    /// when the clip is moved to the trash, unix timestamp in seconds
+    pub deleted_at: i64,
+}
+
+/// get all the clips in the trash, the latest deleted first
+pub fn list_trash(connection: &Connection) -> Result<Vec<TrashClip>, error::Error> {
+    let mut stmt = match connection.prepare(
+        "SELECT id, type, preview, timestamp, deleted_at, size FROM clips
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC, id DESC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+    };
+
+    let res = match stmt.query_map([], |row| {
+        let clip_type: u8 = row.get(1)?;
+        let text: String = row.get(2)?;
+        Ok(TrashClip {
+            clip: Clip {
+                id: row.get(0)?,
+                text: Arc::new(text),
+                size: row.get(5)?,
+                timestamp: row.get(3)?,
+                clip_type: clip_type.into(),
+                labels: Vec::new(),
+                representations: Vec::new(),
+            },
+            deleted_at: row.get(4)?,
+        })
+    }) {
+        Ok(res) => res,
+        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+    };
+
+    let mut clips = Vec::new();
+    for clip in res {
+        match clip {
+            Ok(clip) => clips.push(clip),
+            Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+        }
+    }
+
+    Ok(clips)
+}
+
+/// move the clip out of the trash
+#[warn(unused_must_use)]
+pub fn restore_from_trash(connection: &Connection, id: u64) -> Result<(), error::Error> {
+    match connection.execute(
+        "UPDATE clips SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        [id],
+    ) {
+        Ok(0) => Err(error::Error::ClipNotFoundErr(id as i64)),
+        Ok(_) => Ok(()),
+        Err(err) => Err(error::Error::UpdateClipsInDatabaseErr(
+            id.to_string(),
+            err.to_string(),
+        )),
+    }
+}
+
+/// delete all the clips in the trash, return the number of clips deleted
+#[warn(unused_must_use)]
+pub fn empty_trash(connection: &Connection) -> Result<u64, error::Error> {
+    match connection.execute("DELETE FROM clips WHERE deleted_at IS NOT NULL", []) {
+        Ok(res) => Ok(res as u64),
+        Err(err) => Err(error::Error::DeleteClipFromDatabaseErr(0, err.to_string())),
+    }
+}
+
+/// get all the clips in the trash
+///
+/// input: {}
+///
+/// output: Vec<{
+///     id: u64,
+///     text: String,
+///     timestamp: i64,
+///     clip_type: ClipType,
+///     labels: Vec<String>,
+///     deleted_at: i64,
+/// }>, the latest deleted first
+#[tauri::command]
+pub async fn get_trash_clips(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+) -> Result<Vec<TrashClip>, error::Error> {
+    let clip_state = clip_state.clip_state.lock().await;
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let mut res = list_trash(&db_connection)?;
+    drop(db_connection);
+
+    for trash_clip in res.iter_mut() {
+        trash_clip.clip.labels = clip_state
+            .get_clip_labels(&app, trash_clip.clip.id)
+            .await?
+            .unwrap_or_default();
+    }
+
+    Ok(res)
+}
+
+/// move a clip out of the trash
+///
+/// input: {
+///     id: u64, the id of the clip
+/// }
+#[tauri::command]
+pub async fn restore_clip_from_trash(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    event_sender: tauri::State<'_, EventSender>,
+    id: u64,
+) -> Result<(), error::Error> {
+    let clip_state = clip_state.clip_state.lock().await;
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    restore_from_trash(&db_connection, id)?;
+    drop(db_connection);
+
+    clip_state.trigger_tray_update_event(&app).await;
+    drop(clip_state);
+
+    event_sender
+        .send(CopyClipEvent::SendNotificationEvent(
+            "Clip restored from the trash.".to_string(),
+        ))
+        .await;
+
+    Ok(())
+}
+
+/// delete all the clips in the trash, they can not be restored anymore
+///
+/// input: {}
+///
+/// output: u64, the number of clips deleted
+#[tauri::command]
+pub async fn empty_trash_invoke(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    event_sender: tauri::State<'_, EventSender>,
+) -> Result<u64, error::Error> {
+    // lock the clip state, so the trash does not change during the delete
+    let clip_state = clip_state.clip_state.lock().await;
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let deleted = empty_trash(&db_connection)?;
+    drop(db_connection);
+    drop(clip_state);
+
+    event_sender
+        .send(CopyClipEvent::SendNotificationEvent(format!(
+            "{} clips deleted from the trash.",
+            deleted
+        )))
+        .await;
+
+    Ok(deleted)
+}
@@ -0,0 +1,413 @@
+use std::collections::VecDeque;
+
+use clip::ClipRepresentation;
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+use crate::{
+    database::index_compressed_text,
+    error,
+    event::{CopyClipEvent, EventSender},
+};
+
+use super::{
+    clip_data::ClipStateMutex,
+    format::{get_clip_formats, insert_clip_formats},
+    image::{get_clip_image, insert_clip_image, ClipImage},
+    revision::{list_revisions, ClipRevision},
+    text::full_text,
+};
+
+/// the max number of actions kept in the undo stack, the oldest action is dropped first
+const MAX_UNDO_ACTIONS: usize = 50;
+
+/// a clip hard deleted from the database, with everything needed to insert it back
+#[derive(Debug, Clone)]
+pub struct RemovedClip {
+    pub id: u64,
+    pub clip_type: u8,
+    /// the text column, the preview if the text is compressed
+    pub text: String,
+    pub timestamp: i64,
+    pub hash: String,
+    pub deleted_at: Option<i64>,
+    pub preview: String,
+    pub size: u64,
+    pub compressed_text: Option<Vec<u8>>,
+    pub copy_count: u64,
+    pub labels: Vec<String>,
+    /// the png of an image clip
+    pub image: Option<ClipImage>,
+    /// the representations stored besides the text and the image
+    pub formats: Vec<ClipRepresentation>,
+    /// the previous texts of the clip, deleted with it
+    pub revisions: Vec<ClipRevision>,
+}
+
+/// a destructive action that can be undone
+#[derive(Debug, Clone)]
+pub enum UndoAction {
+    /// a clip moved to the trash
+    TrashClip(u64),
+    /// a label added to or removed from a clip,
+    /// target is true if the label was added
+    ChangeClipLabel {
+        id: u64,
+        label: String,
+        target: bool,
+    },
+    /// a label deleted, with the clips that had the label
+    DeleteLabel { label: String, clip_ids: Vec<u64> },
+    /// the duplicates removed when a new clip is created,
+    /// id is the new clip, their copies were added to its copy_count
+    RemoveDuplicates { id: u64, clips: Vec<RemovedClip> },
+}
+
+impl UndoAction {
+    /// the text shown to the user after the action is undone
+    pub fn description(&self) -> String {
+        match self {
+            UndoAction::TrashClip(_) => "Clip restored from the trash.".to_string(),
+            UndoAction::ChangeClipLabel { label, target, .. } => {
+                if *target {
+                    format!("Label {} removed from the clip again.", label)
+                } else {
+                    format!("Label {} added back to the clip.", label)
+                }
+            }
+            UndoAction::DeleteLabel { label, .. } => format!("Label {} restored.", label),
+            UndoAction::RemoveDuplicates { clips, .. } => {
+                format!("{} duplicate clips restored.", clips.len())
+            }
+        }
+    }
+
+    /// revert the action in the database
+    #[warn(unused_must_use)]
+    pub fn undo(&self, connection: &Connection) -> Result<(), error::Error> {
+        match self {
+            UndoAction::TrashClip(id) => match connection.execute(
+                "UPDATE clips SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+                [id],
+            ) {
+                Ok(0) => Err(error::Error::ClipNotFoundErr(*id as i64)),
+                Ok(_) => Ok(()),
+                Err(err) => Err(error::Error::UpdateClipsInDatabaseErr(
+                    id.to_string(),
+                    err.to_string(),
+                )),
+            },
+            UndoAction::ChangeClipLabel { id, label, target } => {
+                let sql = if *target {
+                    "DELETE FROM clip_labels WHERE clip_id = ? AND label = ?"
+                } else {
+                    "INSERT OR IGNORE INTO clip_labels (clip_id, label) VALUES (?, ?)"
+                };
+                match connection.execute(sql, rusqlite::params![id, label]) {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(error::Error::UpdateClipsInDatabaseErr(
+                        format!("undo label change of id: {} label: {}", id, label),
+                        err.to_string(),
+                    )),
+                }
+            }
+            UndoAction::DeleteLabel { label, clip_ids } => {
+                let transaction = match connection.unchecked_transaction() {
+                    Ok(transaction) => transaction,
+                    Err(err) => return Err(error::Error::DatabaseWriteErr(err.to_string())),
+                };
+                match transaction.execute("INSERT OR IGNORE INTO labels (name) VALUES (?)", [label])
+                {
+                    Ok(_) => (),
+                    Err(err) => return Err(error::Error::DatabaseWriteErr(err.to_string())),
+                };
+                for id in clip_ids {
+                    // the clip may be deleted since the label was deleted
+                    match transaction.execute(
+                        "INSERT OR IGNORE INTO clip_labels (clip_id, label)
+                        SELECT id, ? FROM clips WHERE id = ?",
+                        rusqlite::params![label, id],
+                    ) {
+                        Ok(_) => (),
+                        Err(err) => return Err(error::Error::DatabaseWriteErr(err.to_string())),
+                    };
+                }
+                match transaction.commit() {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(error::Error::DatabaseWriteErr(err.to_string())),
+                }
+            }
+            UndoAction::RemoveDuplicates { id, clips } => {
+                let transaction = match connection.unchecked_transaction() {
+                    Ok(transaction) => transaction,
+                    Err(err) => return Err(error::Error::DatabaseWriteErr(err.to_string())),
+                };
+                for clip in clips {
+                    match transaction.execute(
+                        "INSERT OR IGNORE INTO clips
+                        (id, type, text, timestamp, hash, deleted_at, preview, size, compressed_text, copy_count)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                        rusqlite::params![
+                            clip.id,
+                            clip.clip_type,
+                            clip.text,
+                            clip.timestamp,
+                            clip.hash,
+                            clip.deleted_at,
+                            clip.preview,
+                            clip.size,
+                            clip.compressed_text,
+                            clip.copy_count
+                        ],
+                    ) {
+                        Ok(_) => (),
+                        Err(err) => {
+                            return Err(error::Error::InsertClipIntoDatabaseErr(
+                                clip.text.clone(),
+                                err.to_string(),
+                            ))
+                        }
+                    };
+                    if let Some(compressed_text) = &clip.compressed_text {
+                        let text =
+                            full_text(clip.id, String::new(), Some(compressed_text.clone()))?;
+                        index_compressed_text(&transaction, clip.id, &text)?;
+                    }
+                    if let Some(image) = &clip.image {
+                        insert_clip_image(&transaction, clip.id, image)?;
+                    }
+                    insert_clip_formats(&transaction, clip.id, &clip.formats)?;
+                    for revision in clip.revisions.iter() {
+                        match transaction.execute(
+                            "INSERT OR IGNORE INTO clip_revisions (id, clip_id, text, timestamp)
+                            VALUES (?, ?, ?, ?)",
+                            rusqlite::params![
+                                revision.id,
+                                clip.id,
+                                revision.text,
+                                revision.timestamp
+                            ],
+                        ) {
+                            Ok(_) => (),
+                            Err(err) => {
+                                return Err(error::Error::DatabaseWriteErr(err.to_string()))
+                            }
+                        };
+                    }
+                    for label in clip.labels.iter() {
+                        // the label may be deleted since the clip was removed
+                        match transaction.execute(
+                            "INSERT OR IGNORE INTO clip_labels (clip_id, label)
+                            SELECT ?, name FROM labels WHERE name = ?",
+                            rusqlite::params![clip.id, label],
+                        ) {
+                            Ok(_) => (),
+                            Err(err) => {
+                                return Err(error::Error::DatabaseWriteErr(err.to_string()))
+                            }
+                        };
+                    }
+                }
+                // the copies of the restored clips are not counted by the new clip anymore
+                let copies: u64 = clips.iter().map(|clip| clip.copy_count).sum();
+                match transaction.execute(
+                    "UPDATE clips SET copy_count = MAX(copy_count - ?, 1) WHERE id = ?",
+                    rusqlite::params![copies, id],
+                ) {
+                    Ok(_) => (),
+                    Err(err) => {
+                        return Err(error::Error::UpdateClipsInDatabaseErr(
+                            id.to_string(),
+                            err.to_string(),
+                        ))
+                    }
+                };
+                match transaction.commit() {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(error::Error::DatabaseWriteErr(err.to_string())),
+                }
+            }
+        }
+    }
+}
+
+/// the in memory undo stack, it is lost when the app quits
+#[derive(Debug, Default, Clone)]
+pub struct UndoStack {
+    actions: VecDeque<UndoAction>,
+}
+
+impl UndoStack {
+    /// record a new action, drop the oldest one if the stack is full
+    pub fn push(&mut self, action: UndoAction) {
+        if self.actions.len() >= MAX_UNDO_ACTIONS {
+            self.actions.pop_front();
+        }
+        self.actions.push_back(action);
+    }
+
+    /// take the latest action out of the stack
+    pub fn pop(&mut self) -> Option<UndoAction> {
+        self.actions.pop_back()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// get the clips with the same text as the new clip, they are removed
+/// when auto delete duplicate clip is enabled
+///
+/// the hash is indexed, the text is compared in case of a hash collision,
+/// the clips in the trash are not included
+pub fn get_duplicate_clips(
+    connection: &Connection,
+    hash: &str,
+    text: &str,
+    id: u64,
+) -> Result<Vec<RemovedClip>, error::Error> {
+    let mut stmt = match connection.prepare(
+        "SELECT id, type, text, timestamp, hash, deleted_at, preview, size, compressed_text, copy_count
+        FROM clips
+        WHERE hash = ? AND clip_text(text, compressed_text) = ? AND id != ? AND deleted_at IS NULL",
+    ) {
+        Ok(stmt) => stmt,
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                id,
+                err.to_string(),
+            ))
+        }
+    };
+    let res = match stmt.query_map(rusqlite::params![hash, text, id], |row| {
+        Ok(RemovedClip {
+            id: row.get(0)?,
+            clip_type: row.get(1)?,
+            text: row.get(2)?,
+            timestamp: row.get(3)?,
+            hash: row.get(4)?,
+            deleted_at: row.get(5)?,
+            preview: row.get(6)?,
+            size: row.get(7)?,
+            compressed_text: row.get(8)?,
+            copy_count: row.get(9)?,
+            labels: Vec::new(),
+            image: None,
+            formats: Vec::new(),
+            revisions: Vec::new(),
+        })
+    }) {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                id,
+                err.to_string(),
+            ))
+        }
+    };
+    let mut clips = Vec::new();
+    for clip in res {
+        match clip {
+            Ok(clip) => clips.push(clip),
+            Err(err) => {
+                return Err(error::Error::GetClipDataFromDatabaseErr(
+                    id,
+                    err.to_string(),
+                ))
+            }
+        }
+    }
+
+    let mut stmt = match connection.prepare("SELECT label FROM clip_labels WHERE clip_id = ?") {
+        Ok(stmt) => stmt,
+        Err(err) => {
+            return Err(error::Error::GetClipDataFromDatabaseErr(
+                id,
+                err.to_string(),
+            ))
+        }
+    };
+    for clip in clips.iter_mut() {
+        let res = match stmt.query_map([clip.id], |row| row.get::<_, String>(0)) {
+            Ok(res) => res,
+            Err(err) => {
+                return Err(error::Error::GetClipDataFromDatabaseErr(
+                    clip.id,
+                    err.to_string(),
+                ))
+            }
+        };
+        for label in res {
+            match label {
+                Ok(label) => clip.labels.push(label),
+                Err(err) => {
+                    return Err(error::Error::GetClipDataFromDatabaseErr(
+                        clip.id,
+                        err.to_string(),
+                    ))
+                }
+            }
+        }
+        clip.image = get_clip_image(connection, clip.id)?;
+        clip.formats = get_clip_formats(connection, clip.id)?;
+        clip.revisions = list_revisions(connection, clip.id)?;
+    }
+
+    Ok(clips)
+}
+
+/// get the ids of the clips with the label
+pub fn get_label_clip_ids(connection: &Connection, label: &str) -> Result<Vec<u64>, error::Error> {
+    let mut stmt = match connection.prepare("SELECT clip_id FROM clip_labels WHERE label = ?") {
+        Ok(stmt) => stmt,
+        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+    };
+    let res = match stmt.query_map([label], |row| row.get::<_, u64>(0)) {
+        Ok(res) => res,
+        Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+    };
+    let mut ids = Vec::new();
+    for id in res {
+        match id {
+            Ok(id) => ids.push(id),
+            Err(err) => return Err(error::Error::GetClipDataFromDatabaseErr(0, err.to_string())),
+        }
+    }
+
+    Ok(ids)
+}
+
+/// undo the last delete, label change or duplicate removal
+///
+/// input: {}
+///
+/// output: bool, false if there is nothing to undo
+#[tauri::command]
+pub async fn undo_last_action(
+    app: AppHandle,
+    clip_state: tauri::State<'_, ClipStateMutex>,
+    event_sender: tauri::State<'_, EventSender>,
+) -> Result<bool, error::Error> {
+    let mut clip_state = clip_state.clip_state.lock().await;
+    let res = clip_state.undo_last_action(&app).await?;
+    drop(clip_state);
+
+    let message = match res {
+        Some(message) => message,
+        None => {
+            event_sender
+                .send(CopyClipEvent::SendNotificationEvent(
+                    "Nothing to undo.".to_string(),
+                ))
+                .await;
+            return Ok(false);
+        }
+    };
+
+    event_sender
+        .send(CopyClipEvent::SendNotificationEvent(message))
+        .await;
+
+    Ok(true)
+}
@@ -6,7 +6,7 @@ use tauri_plugin_logging::LogLevelFilter;
 
 use crate::event::{CopyClipEvent, EventSender};
 
-use super::ConfigMutex;
+use super::{ConfigMutex, LargeClipPolicy};
 
 /// get the number of clips to show in the tray menu
 ///
@@ -254,3 +254,332 @@ pub async fn set_auto_delete_duplicate_clip(
 
     Ok(())
 }
+
+/// get max_clip_count, 0 means no limit
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_max_clip_count(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.max_clip_count;
+    drop(config);
+    Ok(res)
+}
+
+/// set max_clip_count, 0 means no limit
+///
+/// the clips breaking the new limit are pruned
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_max_clip_count(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.max_clip_count != data {
+        config.max_clip_count = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+        event_sender.send(CopyClipEvent::PruneClipsEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get max_clip_age_days, 0 means no limit
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_max_clip_age_days(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.max_clip_age_days;
+    drop(config);
+    Ok(res)
+}
+
+/// set max_clip_age_days, 0 means no limit
+///
+/// the clips breaking the new limit are pruned
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_max_clip_age_days(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.max_clip_age_days != data {
+        config.max_clip_age_days = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+        event_sender.send(CopyClipEvent::PruneClipsEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get max_total_text_bytes, 0 means no limit
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_max_total_text_bytes(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.max_total_text_bytes;
+    drop(config);
+    Ok(res)
+}
+
+/// set max_total_text_bytes, 0 means no limit
+///
+/// the clips breaking the new limit are pruned
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_max_total_text_bytes(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.max_total_text_bytes != data {
+        config.max_total_text_bytes = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+        event_sender.send(CopyClipEvent::PruneClipsEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get retention_exempt_labels,
+/// the clips with any of these labels are never pruned
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_retention_exempt_labels(
+    config: State<'_, ConfigMutex>,
+) -> Result<Vec<String>, String> {
+    let config = config.config.lock().await;
+    let res = config.retention_exempt_labels.clone();
+    drop(config);
+    Ok(res)
+}
+
+/// set retention_exempt_labels
+///
+/// input: { data: Vec<String> }
+#[tauri::command]
+pub async fn set_retention_exempt_labels(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: Vec<String>,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.retention_exempt_labels != data {
+        config.retention_exempt_labels = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+        event_sender.send(CopyClipEvent::PruneClipsEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get trash_auto_empty_days, 0 means the trash is never emptied automatically
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_trash_auto_empty_days(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.trash_auto_empty_days;
+    drop(config);
+    Ok(res)
+}
+
+/// set trash_auto_empty_days, 0 means the trash is never emptied automatically
+///
+/// the clips in the trash longer than the new limit are deleted
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_trash_auto_empty_days(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.trash_auto_empty_days != data {
+        config.trash_auto_empty_days = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+        event_sender.send(CopyClipEvent::PruneClipsEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get backup_keep_daily, 0 means no daily backup
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_backup_keep_daily(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.backup_keep_daily;
+    drop(config);
+    Ok(res)
+}
+
+/// set backup_keep_daily, 0 means no daily backup
+///
+/// the daily backups more than the new limit are deleted
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_backup_keep_daily(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.backup_keep_daily != data {
+        config.backup_keep_daily = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+        event_sender.send(CopyClipEvent::BackupDatabaseEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get backup_keep_weekly, 0 means no weekly backup
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_backup_keep_weekly(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.backup_keep_weekly;
+    drop(config);
+    Ok(res)
+}
+
+/// set backup_keep_weekly, 0 means no weekly backup
+///
+/// the weekly backups more than the new limit are deleted
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_backup_keep_weekly(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.backup_keep_weekly != data {
+        config.backup_keep_weekly = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+        event_sender.send(CopyClipEvent::BackupDatabaseEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get max_clip_size in bytes, 0 means no limit
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_max_clip_size(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.max_clip_size;
+    drop(config);
+    Ok(res)
+}
+
+/// set max_clip_size in bytes, 0 means no limit
+///
+/// only the new clips are affected
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_max_clip_size(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.max_clip_size != data {
+        config.max_clip_size = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get large_clip_policy, "skip" or "truncate"
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_large_clip_policy(config: State<'_, ConfigMutex>) -> Result<String, String> {
+    let config = config.config.lock().await;
+    let res = config.large_clip_policy.to_string();
+    drop(config);
+    Ok(res)
+}
+
+/// set large_clip_policy
+///
+/// input: {
+///     data: String, "skip" or "truncate"
+/// }
+#[tauri::command]
+pub async fn set_large_clip_policy(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: String,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    let policy = LargeClipPolicy::from(data);
+    if config.large_clip_policy != policy {
+        config.large_clip_policy = policy;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+    }
+
+    Ok(())
+}
+
+/// get compress_threshold in bytes, 0 means the texts are never compressed
+///
+/// input: {}
+#[tauri::command]
+pub async fn get_compress_threshold(config: State<'_, ConfigMutex>) -> Result<u64, String> {
+    let config = config.config.lock().await;
+    let res = config.compress_threshold;
+    drop(config);
+    Ok(res)
+}
+
+/// set compress_threshold in bytes, 0 means the texts are never compressed
+///
+/// the existing clips are not compressed or decompressed again
+///
+/// input: { data: u64 }
+#[tauri::command]
+pub async fn set_compress_threshold(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigMutex>,
+    data: u64,
+) -> Result<(), String> {
+    let mut config = config.config.lock().await;
+    if config.compress_threshold != data {
+        config.compress_threshold = data;
+        let event_sender = app.state::<EventSender>();
+        event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+    }
+
+    Ok(())
+}
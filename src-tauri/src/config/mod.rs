@@ -33,6 +33,66 @@ fn default_auto_delete_duplicate_clip() -> bool {
 fn default_pause_monitoring() -> bool {
     false
 }
+fn default_max_clip_count() -> u64 {
+    0
+}
+fn default_max_clip_age_days() -> u64 {
+    0
+}
+fn default_max_total_text_bytes() -> u64 {
+    0
+}
+fn default_retention_exempt_labels() -> Vec<String> {
+    vec!["pinned".to_string(), "favourite".to_string()]
+}
+fn default_trash_auto_empty_days() -> u64 {
+    30
+}
+fn default_backup_keep_daily() -> u64 {
+    7
+}
+fn default_backup_keep_weekly() -> u64 {
+    4
+}
+fn default_max_clip_size() -> u64 {
+    0
+}
+fn default_large_clip_policy() -> LargeClipPolicy {
+    LargeClipPolicy::Truncate
+}
+fn default_compress_threshold() -> u64 {
+    64 * 1024
+}
+
+/// what to do with a new clip larger than the max clip size
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LargeClipPolicy {
+    /// the clip is not saved
+    #[serde(rename = "skip")]
+    Skip,
+    /// the text is cut to the max clip size,
+    /// the clips which can not be cut, like the images, are not saved
+    #[serde(rename = "truncate")]
+    Truncate,
+}
+
+impl From<String> for LargeClipPolicy {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "skip" => Self::Skip,
+            _ => Self::Truncate,
+        }
+    }
+}
+
+impl std::fmt::Display for LargeClipPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Skip => write!(f, "skip"),
+            Self::Truncate => write!(f, "truncate"),
+        }
+    }
+}
 
 /// the config struct
 pub struct ConfigMutex {
@@ -71,6 +131,44 @@ pub struct Config {
     /// Allow user to pause monitoring without exiting the app.
     #[serde(default = "default_pause_monitoring")]
     pub pause_monitoring: bool,
+    /// the max number of clips to keep, the oldest clips are deleted first,
+    /// 0 means no limit
+    #[serde(default = "default_max_clip_count")]
+    pub max_clip_count: u64,
+    /// the max age of a clip in days, older clips are deleted,
+    /// 0 means no limit
+    #[serde(default = "default_max_clip_age_days")]
+    pub max_clip_age_days: u64,
+    /// the max total size of the text of all clips in bytes, the oldest clips are deleted first,
+    /// 0 means no limit
+    #[serde(default = "default_max_total_text_bytes")]
+    pub max_total_text_bytes: u64,
+    /// the clips with any of these labels are never deleted by the retention policy
+    #[serde(default = "default_retention_exempt_labels")]
+    pub retention_exempt_labels: Vec<String>,
+    /// the clips in the trash longer than this number of days are deleted,
+    /// 0 means the trash is never emptied automatically
+    #[serde(default = "default_trash_auto_empty_days")]
+    pub trash_auto_empty_days: u64,
+    /// the number of daily database backups to keep,
+    /// 0 means no daily backup
+    #[serde(default = "default_backup_keep_daily")]
+    pub backup_keep_daily: u64,
+    /// the number of weekly database backups to keep,
+    /// 0 means no weekly backup
+    #[serde(default = "default_backup_keep_weekly")]
+    pub backup_keep_weekly: u64,
+    /// the max size of a new clip in bytes, the larger clips are handled by the large_clip_policy,
+    /// 0 means no limit
+    #[serde(default = "default_max_clip_size")]
+    pub max_clip_size: u64,
+    /// what to do with a new clip larger than the max_clip_size
+    #[serde(default = "default_large_clip_policy")]
+    pub large_clip_policy: LargeClipPolicy,
+    /// the texts larger than this number of bytes are compressed in the database,
+    /// 0 means the texts are never compressed
+    #[serde(default = "default_compress_threshold")]
+    pub compress_threshold: u64,
 }
 
 /// the default config
@@ -86,6 +184,16 @@ impl Default for Config {
             language: default_language(),
             auto_delete_duplicate_clip: default_auto_delete_duplicate_clip(),
             pause_monitoring: default_pause_monitoring(),
+            max_clip_count: default_max_clip_count(),
+            max_clip_age_days: default_max_clip_age_days(),
+            max_total_text_bytes: default_max_total_text_bytes(),
+            retention_exempt_labels: default_retention_exempt_labels(),
+            trash_auto_empty_days: default_trash_auto_empty_days(),
+            backup_keep_daily: default_backup_keep_daily(),
+            backup_keep_weekly: default_backup_keep_weekly(),
+            max_clip_size: default_max_clip_size(),
+            large_clip_policy: default_large_clip_policy(),
+            compress_threshold: default_compress_threshold(),
         }
     }
 }
@@ -0,0 +1,443 @@
+use log::{debug, error, warn};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    clip::{clip_data::ClipStateMutex, text::PREVIEW_MAX_CHARS},
+    error::Error,
+};
+
+use super::{
+    init_clip_formats_table, init_clip_images_table, init_clip_labels_table,
+    init_clip_revisions_table, init_clips_deleted_at_index, init_clips_fts_table,
+    init_clips_hash_index, init_clips_table, init_labels_table, rebuild_clips_fts_table,
+    remove_orphan_label_rows, DatabaseStateMutex, DEFAULT_LABELS,
+};
+
+/// the tables every database should have
+const EXPECTED_TABLES: [&str; 8] = [
+    "version",
+    "clips",
+    "labels",
+    "clip_labels",
+    "clips_fts",
+    "clip_revisions",
+    "clip_images",
+    "clip_formats",
+];
+
+/// the columns the clips table should have
+const EXPECTED_CLIPS_COLUMNS: [&str; 10] = [
+    "id",
+    "type",
+    "text",
+    "timestamp",
+    "hash",
+    "deleted_at",
+    "preview",
+    "size",
+    "compressed_text",
+    "copy_count",
+];
+
+/// the indexes every database should have
+const EXPECTED_INDEXES: [&str; 4] = [
+    "clips_hash_index",
+    "clips_deleted_at_index",
+    "clip_labels_label_index",
+    "clip_revisions_clip_id_index",
+];
+
+/// the triggers keeping the clips_fts table in sync with the clips table
+const EXPECTED_TRIGGERS: [&str; 3] = ["clips_fts_insert", "clips_fts_delete", "clips_fts_update"];
+
+/// a problem found in the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "detail")]
+pub enum IntegrityProblem {
+    /// a row returned by `PRAGMA integrity_check`,
+    /// the database file is corrupted, can not be repaired by the app
+    Corruption(String),
+    /// a table is missing
+    MissingTable(String),
+    /// a column of the clips table is missing
+    MissingColumn(String),
+    /// an index is missing
+    MissingIndex(String),
+    /// a trigger of the full text search table is missing
+    MissingTrigger(String),
+    /// a default label is missing in the labels table
+    MissingDefaultLabel(String),
+    /// the number of rows in the clip_labels table whose clip or label does not exist
+    OrphanLabelRows(u64),
+    /// the full text search index does not match the clips table
+    FullTextIndexOutOfSync(String),
+}
+
+/// the result of `check_database_integrity`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// the problems found before any repair
+    pub problems: Vec<IntegrityProblem>,
+    /// whether the repair was run
+    pub repaired: bool,
+    /// the problems still found after the repair,
+    /// the same as problems if the repair was not run
+    pub remaining: Vec<IntegrityProblem>,
+}
+
+impl IntegrityReport {
+    /// no problem is left in the database
+    pub fn is_ok(&self) -> bool {
+        self.remaining.is_empty()
+    }
+}
+
+/// test if an object with the type and the name is in the sqlite_master table
+fn schema_object_exists(connection: &Connection, kind: &str, name: &str) -> Result<bool, Error> {
+    match connection.query_row(
+        "SELECT name FROM sqlite_master WHERE type = ? AND name = ?",
+        [kind, name],
+        |_| Ok(()),
+    ) {
+        Ok(_) => Ok(true),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+        Err(err) => Err(Error::IntegrityCheckErr(err.to_string())),
+    }
+}
+
+/// run `PRAGMA integrity_check`, return the error rows, empty if the database is ok
+fn integrity_check(connection: &Connection) -> Result<Vec<String>, Error> {
+    let mut stmt = match connection.prepare("PRAGMA integrity_check") {
+        Ok(stmt) => stmt,
+        Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+    };
+    let rows = match stmt.query_map([], |row| row.get::<_, String>(0)) {
+        Ok(rows) => rows,
+        Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+    };
+
+    let mut res = Vec::new();
+    for row in rows {
+        match row {
+            Ok(row) if row == "ok" => (),
+            Ok(row) => res.push(row),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        }
+    }
+
+    Ok(res)
+}
+
+/// find all the problems in the database
+fn find_problems(connection: &Connection) -> Result<Vec<IntegrityProblem>, Error> {
+    let mut problems: Vec<IntegrityProblem> = integrity_check(connection)?
+        .into_iter()
+        .map(IntegrityProblem::Corruption)
+        .collect();
+
+    let mut missing_tables = Vec::new();
+    for table in EXPECTED_TABLES {
+        if !schema_object_exists(connection, "table", table)? {
+            missing_tables.push(table);
+            problems.push(IntegrityProblem::MissingTable(table.to_string()));
+        }
+    }
+
+    if !missing_tables.contains(&"clips") {
+        let mut stmt = match connection.prepare("SELECT name FROM pragma_table_info('clips')") {
+            Ok(stmt) => stmt,
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+        let columns = match stmt.query_map([], |row| row.get::<_, String>(0)) {
+            Ok(columns) => columns,
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+        let mut existing_columns = Vec::new();
+        for column in columns {
+            match column {
+                Ok(column) => existing_columns.push(column),
+                Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+            }
+        }
+        for column in EXPECTED_CLIPS_COLUMNS {
+            if !existing_columns.iter().any(|c| c == column) {
+                problems.push(IntegrityProblem::MissingColumn(column.to_string()));
+            }
+        }
+    }
+
+    for index in EXPECTED_INDEXES {
+        if !schema_object_exists(connection, "index", index)? {
+            problems.push(IntegrityProblem::MissingIndex(index.to_string()));
+        }
+    }
+
+    for trigger in EXPECTED_TRIGGERS {
+        if !schema_object_exists(connection, "trigger", trigger)? {
+            problems.push(IntegrityProblem::MissingTrigger(trigger.to_string()));
+        }
+    }
+
+    if !missing_tables.contains(&"labels") {
+        for label in DEFAULT_LABELS {
+            match connection.query_row(
+                "SELECT name FROM labels WHERE name = ?",
+                [label],
+                |_| Ok(()),
+            ) {
+                Ok(_) => (),
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    problems.push(IntegrityProblem::MissingDefaultLabel(label.to_string()))
+                }
+                Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+            };
+        }
+    }
+
+    if !missing_tables
+        .iter()
+        .any(|table| *table == "clips" || *table == "labels" || *table == "clip_labels")
+    {
+        let orphans: u64 = match connection.query_row(
+            "SELECT COUNT(1) FROM clip_labels
+            WHERE clip_id NOT IN (SELECT id FROM clips)
+                OR label NOT IN (SELECT name FROM labels)",
+            [],
+            |row| row.get(0),
+        ) {
+            Ok(orphans) => orphans,
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+        if orphans > 0 {
+            problems.push(IntegrityProblem::OrphanLabelRows(orphans));
+        }
+    }
+
+    if !missing_tables.contains(&"clips") && !missing_tables.contains(&"clips_fts") {
+        // compare the full text index with the content of the clips table
+        if let Err(err) = connection.execute(
+            "INSERT INTO clips_fts (clips_fts, rank) VALUES ('integrity-check', 1)",
+            [],
+        ) {
+            problems.push(IntegrityProblem::FullTextIndexOutOfSync(err.to_string()));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// try to fix the problems which can be fixed without losing data
+///
+/// this function will
+///     - recreate the missing tables, except the version table, indexes and triggers
+///     - add the preview, size and compressed_text columns to the clips table,
+///       and fill the preview and the size
+///     - add the hash column to the clips table, and compute the hash
+///     - add the deleted_at column to the clips table
+///     - add the copy_count column to the clips table
+///     - rebuild all the indexes
+///     - recreate the missing default labels
+///     - remove the orphan rows in the clip_labels table
+///     - rebuild the full text search index
+#[warn(unused_must_use)]
+fn repair(connection: &Connection, problems: &[IntegrityProblem]) -> Result<(), Error> {
+    // added before the hash, which is computed from the full text
+    if problems.contains(&IntegrityProblem::MissingColumn(
+        "compressed_text".to_string(),
+    )) {
+        match connection.execute(
+            "ALTER TABLE clips ADD COLUMN compressed_text BLOB DEFAULT NULL",
+            [],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+    }
+
+    let missing_preview =
+        problems.contains(&IntegrityProblem::MissingColumn("preview".to_string()));
+    if missing_preview {
+        match connection.execute(
+            "ALTER TABLE clips ADD COLUMN preview TEXT NOT NULL DEFAULT ''",
+            [],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+    }
+
+    let missing_size = problems.contains(&IntegrityProblem::MissingColumn("size".to_string()));
+    if missing_size {
+        match connection.execute(
+            "ALTER TABLE clips ADD COLUMN size INTEGER NOT NULL DEFAULT 0",
+            [],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+    }
+
+    if missing_preview || missing_size {
+        match connection.execute(
+            "UPDATE clips SET preview = substr(clip_text(text, compressed_text), 1, ?),
+                size = length(CAST(clip_text(text, compressed_text) AS BLOB))",
+            [PREVIEW_MAX_CHARS as i64],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+    }
+
+    if problems.contains(&IntegrityProblem::MissingColumn("hash".to_string())) {
+        match connection.execute(
+            "ALTER TABLE clips ADD COLUMN hash TEXT NOT NULL DEFAULT ''",
+            [],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+        match connection.execute(
+            "UPDATE clips SET hash = clip_hash(clip_text(text, compressed_text))",
+            [],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+    }
+
+    if problems.contains(&IntegrityProblem::MissingColumn("deleted_at".to_string())) {
+        match connection.execute(
+            "ALTER TABLE clips ADD COLUMN deleted_at INTEGER DEFAULT NULL",
+            [],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+    }
+
+    if problems.contains(&IntegrityProblem::MissingColumn("copy_count".to_string())) {
+        match connection.execute(
+            "ALTER TABLE clips ADD COLUMN copy_count INTEGER NOT NULL DEFAULT 1",
+            [],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::IntegrityCheckErr(err.to_string())),
+        };
+    }
+
+    // all of them only create what is missing
+    init_clips_table(connection)?;
+    init_clips_fts_table(connection)?;
+    init_clips_hash_index(connection)?;
+    init_clips_deleted_at_index(connection)?;
+    init_labels_table(connection)?;
+    init_clip_labels_table(connection)?;
+    init_clip_revisions_table(connection)?;
+    init_clip_images_table(connection)?;
+    init_clip_formats_table(connection)?;
+
+    if let Err(err) = connection.execute("REINDEX", []) {
+        return Err(Error::IntegrityCheckErr(err.to_string()));
+    }
+
+    remove_orphan_label_rows(connection)?;
+
+    let fts_problem = problems.iter().any(|problem| {
+        matches!(
+            problem,
+            IntegrityProblem::FullTextIndexOutOfSync(_)
+                | IntegrityProblem::MissingTable(_)
+                | IntegrityProblem::MissingTrigger(_)
+        )
+    });
+    if fts_problem {
+        rebuild_clips_fts_table(connection)?;
+    }
+
+    Ok(())
+}
+
+/// check the database, and repair the problems if `repair_problems` is true
+///
+/// the check includes
+///     - `PRAGMA integrity_check`
+///     - the tables, the columns of the clips table, the indexes and the triggers
+///     - the default labels
+///     - the orphan rows in the clip_labels table
+///     - the full text search index
+pub fn check_database_integrity(
+    connection: &Connection,
+    repair_problems: bool,
+) -> Result<IntegrityReport, Error> {
+    let problems = find_problems(connection)?;
+    if problems.is_empty() || !repair_problems {
+        return Ok(IntegrityReport {
+            remaining: problems.clone(),
+            problems,
+            repaired: false,
+        });
+    }
+
+    debug!("repair the database, problems: {:?}", problems);
+    repair(connection, &problems)?;
+    let remaining = find_problems(connection)?;
+
+    Ok(IntegrityReport {
+        problems,
+        repaired: true,
+        remaining,
+    })
+}
+
+/// check and repair the database when the app starts,
+/// the problems are logged, the app still starts if some problems can not be repaired
+pub fn startup_check(connection: &Connection) {
+    let report = match check_database_integrity(connection, true) {
+        Ok(report) => report,
+        Err(err) => {
+            error!("{}", err);
+            return;
+        }
+    };
+    if report.problems.is_empty() {
+        return;
+    }
+
+    let report_json = serde_json::to_string(&report).unwrap_or_default();
+    if report.is_ok() {
+        warn!("database problems found and repaired: {}", report_json);
+    } else {
+        error!("database problems can not be repaired: {}", report_json);
+    }
+}
+
+/// check the database, and repair the problems if repair is true
+///
+/// input: {
+///     repair: bool, whether to repair the problems found
+/// }
+///
+/// output: {
+///     problems: Vec<{ kind: String, detail: String | u64 }>,
+///     repaired: bool,
+///     remaining: Vec<{ kind: String, detail: String | u64 }>,
+/// }
+#[tauri::command]
+pub async fn check_database(app: AppHandle, repair: bool) -> Result<IntegrityReport, Error> {
+    // lock the clip state, so no new clip is created during the check
+    let clip_state = app.state::<ClipStateMutex>();
+    let clip_state = clip_state.clip_state.lock().await;
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+    let report = check_database_integrity(&db_connection, repair)?;
+    drop(db_connection);
+
+    if report.repaired {
+        clip_state.trigger_tray_update_event(&app).await;
+    }
+
+    Ok(report)
+}
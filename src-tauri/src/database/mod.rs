@@ -1,9 +1,10 @@
-use data_encoding::BASE32;
 /// The database module is used to deal with the database connection and the database table
 /// Database design:
+///   - PRAGMA user_version
+///     - the schema version, see `backward::CURRENT_SCHEMA_VERSION`
 ///   - version table
 ///     - id INTEGER PRIMARY KEY
-///     - version TEXT
+///     - version TEXT, the app versions which opened the database
 ///  - labels table
 ///     - used to store the labels
 ///     - name TEXT PRIMARY KEY
@@ -11,18 +12,67 @@ use data_encoding::BASE32;
 ///  - clips table
 ///     - id INTEGER PRIMARY KEY AUTOINCREMENT
 ///     - type INTEGER
-///     - text TEXT
+///     - text TEXT, the full text, or the preview if the text is compressed
 ///     - timestamp INTEGER
-///  - label_base32(label_name) tables for each label
-///     - used to store the clips id for each label
-///     - id INTEGER PRIMARY KEY foreign key to clips table
+///     - hash TEXT, the sha256 of the text in lower case hex, indexed
+///     - deleted_at INTEGER, when the clip is moved to the trash, NULL if not in the trash, indexed
+///     - preview TEXT, the first chars of the text, shown in the tray and the search list
+///     - size INTEGER, the size of the full text in bytes
+///     - compressed_text BLOB, the full text compressed with zlib, NULL if the text is not compressed
+///     - copy_count INTEGER, the number of times the text was copied,
+///       including the duplicates deleted when this clip was created
+///  - clips_fts table
+///     - fts5 virtual table, used for the full text search
+///     - contentless table, rowid is the id of the clip
+///     - kept in sync with the clips table by triggers in plain sql, indexing the text column
+///     - the app replaces the preview of the compressed clips with their full text
+///  - clip_labels table
+///     - used to store the labels of each clip
+///     - clip_id INTEGER foreign key to clips table
+///     - label TEXT foreign key to labels table
+///     - PRIMARY KEY (clip_id, label)
+///  - clip_revisions table
+///     - used to store the previous texts of the edited clips
+///     - id INTEGER PRIMARY KEY AUTOINCREMENT
+///     - clip_id INTEGER foreign key to clips table, indexed
+///     - text TEXT, the text before the edit
+///     - timestamp INTEGER, when the text is replaced
+///  - clip_images table
+///     - used to store the png of the image clips, the text of an image clip is a short description
+///     - clip_id INTEGER PRIMARY KEY, foreign key to clips table
+///     - data BLOB, the png
+///     - width INTEGER
+///     - height INTEGER
+///     - size INTEGER, the size of the png in bytes
+///  - clip_formats table
+///     - used to store the representations of a clip besides its text and image,
+///       like the html and rtf of a rich text clip, or the png copied together with a text
+///     - clip_id INTEGER foreign key to clips table
+///     - format TEXT, the mime type, like text/html or text/rtf
+///     - data BLOB, the payload
+///     - PRIMARY KEY (clip_id, format)
 use log::debug;
 use rusqlite::Connection;
+use sha2::{Digest, Sha256};
 use tauri::{AppHandle, Manager};
 
-use crate::backward::backward_comparability;
+use crate::backup::{
+    backup_to_file, get_and_create_backup_dir, rotate_backups, BackupKind, KEEP_UPGRADE_BACKUPS,
+    UPGRADE_PREFIX,
+};
+use crate::backward::{
+    backward_comparability, get_schema_version, legacy_schema_version, set_schema_version,
+    upgrade_database, CURRENT_SCHEMA_VERSION,
+};
+use crate::clip::text::full_text;
 use crate::error::Error;
 
+pub mod integrity;
+
+/// the labels created for every database,
+/// they are used by the tray and can not be renamed or deleted
+pub const DEFAULT_LABELS: [&str; 2] = ["pinned", "favourite"];
+
 type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 /// database state mutex
@@ -66,16 +116,54 @@ pub fn init_database_connection(app: &AppHandle) -> Result<Connection, Error> {
     // create the regexp function
     create_regexp_function(&connection)?;
 
+    // create the clip hash function
+    create_clip_hash_function(&connection)?;
+
+    // create the clip text function
+    create_clip_text_function(&connection)?;
+
     // init the version of the database
     // this will also deal with the backward comparability
-    init_version_table(&connection, app)?;
+    if let Err(err) = init_version_table(&connection, app) {
+        // restore the database file if the upgrade failed
+        if let Error::UpgradeDatabaseErr(_, backup_path, _) = &err {
+            drop(connection);
+            restore_database_file(&app_data_dir, backup_path)?;
+        }
+        return Err(err);
+    }
 
     // init the clips table
     init_clips_table(&connection)?;
 
+    // init the full text search table
+    init_clips_fts_table(&connection)?;
+
     // init the labels table
     init_labels_table(&connection)?;
 
+    // init the clip revisions table
+    init_clip_revisions_table(&connection)?;
+
+    // init the clip images table
+    init_clip_images_table(&connection)?;
+
+    // init the clip formats table
+    init_clip_formats_table(&connection)?;
+
+    // remove the rows left by the deletes before the foreign keys were enforced
+    let orphans = remove_orphan_label_rows(&connection)?;
+    if orphans > 0 {
+        debug!("removed {} orphan rows from the clip_labels table", orphans);
+    }
+
+    // enforce the foreign keys after the migrations,
+    // as some migrations rename and drop the referenced tables
+    enable_foreign_keys(&connection)?;
+
+    // check the database, and repair what can be repaired
+    integrity::startup_check(&connection);
+
     let res = connection.cache_flush();
     if let Err(err) = res {
         return Err(Error::DatabaseWriteErr(err.to_string()));
@@ -140,6 +228,61 @@ fn create_regexp_function(db: &Connection) -> Result<(), Error> {
     }
 }
 
+/// the hash of the text of a clip,
+/// the sha256 of the text in lower case hex
+///
+/// used to find the clips with the same text without comparing the whole text
+pub fn clip_hash(text: &str) -> String {
+    data_hash(text.as_bytes())
+}
+
+/// the sha256 of the data in lower case hex,
+/// used as the hash of the clips which are not text, like the png of an image clip
+pub fn data_hash(data: &[u8]) -> String {
+    let digest = Sha256::digest(data);
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// create the clip_hash(text) function, which is the same as `clip_hash`,
+/// so the hash can be computed in sql, for instance when backfilling the hash column
+fn create_clip_hash_function(connection: &Connection) -> Result<(), Error> {
+    match connection.create_scalar_function(
+        "clip_hash",
+        1,
+        rusqlite::functions::FunctionFlags::SQLITE_UTF8
+            | rusqlite::functions::FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text = ctx.get::<String>(0)?;
+            Ok(clip_hash(&text))
+        },
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// create the clip_text(text, compressed_text) function, which returns the full text of a clip,
+/// so the searches can match the whole text of the compressed clips
+fn create_clip_text_function(connection: &Connection) -> Result<(), Error> {
+    match connection.create_scalar_function(
+        "clip_text",
+        2,
+        rusqlite::functions::FunctionFlags::SQLITE_UTF8
+            | rusqlite::functions::FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text = ctx.get::<String>(0)?;
+            let compressed_text = ctx.get::<Option<Vec<u8>>>(1)?;
+            match full_text(0, text, compressed_text) {
+                Ok(text) => Ok(text),
+                Err(err) => Err(rusqlite::Error::UserFunctionError(err.message().into())),
+            }
+        },
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
 /// get the app data dir and create it if it does not exist
 fn get_and_create_app_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, Error> {
     // get the app data dir
@@ -159,10 +302,51 @@ fn get_and_create_app_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, Er
     Ok(app_data_dir)
 }
 
+/// get the path of the database file
+fn get_database_path(app_data_dir: &std::path::Path) -> std::path::PathBuf {
+    app_data_dir.join("database")
+}
+
+/// copy the database to a timestamped file in the backups dir before upgrading it,
+/// only the newest KEEP_UPGRADE_BACKUPS of these backups are kept
+///
+/// return the path of the backup
+#[warn(unused_must_use)]
+fn backup_before_upgrade(
+    connection: &Connection,
+    app: &AppHandle,
+    save_version: &str,
+) -> Result<String, Error> {
+    let backup_dir = get_and_create_backup_dir(app)?;
+    let backup_path = backup_dir.join(format!(
+        "{}{}-{}",
+        UPGRADE_PREFIX,
+        save_version,
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    ));
+    backup_to_file(connection, &backup_path)?;
+    rotate_backups(&backup_dir, BackupKind::Upgrade, KEEP_UPGRADE_BACKUPS)?;
+
+    Ok(backup_path.to_string_lossy().to_string())
+}
+
+/// replace the database file with the backup,
+/// the connection to the database must be closed before calling this function
+fn restore_database_file(app_data_dir: &std::path::Path, backup_path: &str) -> Result<(), Error> {
+    let database_path = get_database_path(app_data_dir);
+    match std::fs::copy(backup_path, database_path) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::RestoreDatabaseErr(
+            backup_path.to_string(),
+            err.to_string(),
+        )),
+    }
+}
+
 /// get the database connection and create it if it does not exist
 fn get_and_create_database(app_data_dir: std::path::PathBuf) -> Result<Connection, Error> {
     // create the database dir if it does not exist
-    let database_path = app_data_dir.join("database");
+    let database_path = get_database_path(&app_data_dir);
 
     // TODO test if the database file does not exist
 
@@ -173,7 +357,7 @@ fn get_and_create_database(app_data_dir: std::path::PathBuf) -> Result<Connectio
 }
 
 /// get the current version from the tauri config
-fn get_current_version(app: &AppHandle) -> Result<String, Error> {
+pub fn get_current_version(app: &AppHandle) -> Result<String, Error> {
     let current_version = app.config().package.version.clone();
     if current_version.is_none() {
         return Err(Error::GetVersionFromTauriErr);
@@ -227,9 +411,11 @@ fn insert_version(connection: &Connection, version: String) -> Result<(), Error>
 }
 
 /// this function will
-///     - check if the save version is the same as the current version
-///     - if not, trigger backward_compatibility and update the version table
-///     - if yes, do nothing
+///     - find the schema version of the database,
+///       from the version table if the database is saved before the schema version was stored
+///     - refuse the database saved by a newer schema version
+///     - if the schema version is older, back up the database and trigger backward_comparability
+///     - update the version table if the save version is not the same as the current version
 #[warn(unused_must_use)]
 fn check_save_version_and_current_version(
     save_version: String,
@@ -238,28 +424,93 @@ fn check_save_version_and_current_version(
 ) -> Result<(), Error> {
     let current_version = get_current_version(app)?;
 
+    let mut schema_version = get_schema_version(connection)?;
+    if schema_version == 0 {
+        schema_version = legacy_schema_version(&save_version)?;
+    }
+
     debug!("save version: {}", save_version);
     debug!("current version: {}", current_version);
+    debug!("schema version: {}", schema_version);
+
+    if schema_version > CURRENT_SCHEMA_VERSION {
+        return Err(Error::DatabaseSchemaTooNewErr(
+            schema_version,
+            CURRENT_SCHEMA_VERSION,
+        ));
+    }
+
+    if schema_version < CURRENT_SCHEMA_VERSION {
+        // copy the database before the upgrade, so it can be restored if the upgrade fails
+        let backup_path = backup_before_upgrade(connection, app, &save_version)?;
+        debug!("database backed up to {}", backup_path);
+
+        // if the schema version is older than the current schema version, trigger the backward comparability
+        if let Err(err) = backward_comparability(app, connection, schema_version) {
+            return Err(Error::UpgradeDatabaseErr(
+                save_version,
+                backup_path,
+                err.message(),
+            ));
+        }
+    } else {
+        // the database saved before the schema version was stored may not have it
+        set_schema_version(connection, CURRENT_SCHEMA_VERSION)?;
+    }
 
     if current_version == save_version {
         return Ok(());
     }
 
-    // if the save version is not the same as the current version, trigger the backward comparability
-    backward_comparability(app, connection, save_version)?;
-
     // update the version table
     insert_version(connection, current_version)
 }
 
+/// bring a database restored from a backup to the current schema version,
+/// the backups taken before an upgrade have an older schema version
+///
+/// only the database migrations are run, the config file already matches the current version,
+/// the caller keeps the replaced database, so no backup is taken before the upgrade
+#[warn(unused_must_use)]
+pub fn upgrade_restored_database(connection: &Connection, app: &AppHandle) -> Result<(), Error> {
+    let current_version = get_current_version(app)?;
+    let save_version = get_save_version(connection)?;
+
+    let mut schema_version = get_schema_version(connection)?;
+    if schema_version == 0 {
+        schema_version = legacy_schema_version(&save_version)?;
+    }
+    debug!("restored schema version: {}", schema_version);
+
+    if schema_version == CURRENT_SCHEMA_VERSION {
+        // the database saved before the schema version was stored may not have it
+        set_schema_version(connection, CURRENT_SCHEMA_VERSION)?;
+    } else {
+        // some migrations rename and drop the referenced tables
+        match connection.pragma_update(None, "foreign_keys", false) {
+            Ok(_) => (),
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        let res = upgrade_database(connection, schema_version);
+        enable_foreign_keys(connection)?;
+        res?;
+    }
+
+    if current_version == save_version {
+        return Ok(());
+    }
+
+    insert_version(connection, current_version)
+}
+
 /// init the version table
 ///
 /// this function will
 ///     - create the version table if it does not exist
 ///     - get the save version
-///     - if the save version is 0.0.0, it is the first time the app is launched, init the version table
-///     - if the save version is not 0.0.0, check if the save version is the same as the current version
-///     - if not, trigger backward_comparability and update the version table
+///     - if the save version is 0.0.0 and there is no schema version,
+///       it is the first time the app is launched, init the version table and the schema version
+///     - if not, check the schema version, trigger backward_comparability if needed and update the version table
 #[warn(unused_must_use)]
 fn init_version_table(connection: &Connection, app: &AppHandle) -> Result<(), Error> {
     // create the version table if it does not exist
@@ -274,9 +525,11 @@ fn init_version_table(connection: &Connection, app: &AppHandle) -> Result<(), Er
     // try get the save version
     let save_version = get_save_version(connection)?;
 
-    if save_version == *"0.0.0" {
-        // first launch the app, init the version table
+    if save_version == *"0.0.0" && get_schema_version(connection)? == 0 {
+        // first launch the app, init the version table,
+        // the tables are created with the current schema
         first_lunch_the_version_table(connection, app)?;
+        set_schema_version(connection, CURRENT_SCHEMA_VERSION)?;
     } else {
         // not the first time the app is launched, check the save version and the current version
         check_save_version_and_current_version(save_version, connection, app)?;
@@ -289,23 +542,163 @@ fn init_version_table(connection: &Connection, app: &AppHandle) -> Result<(), Er
 ///
 /// this function will
 ///     - create the clips table if it does not exist
+///     - create the indexes on the hash and deleted_at columns if they do not exist
 #[warn(unused_must_use)]
-fn init_clips_table(connection: &Connection) -> Result<(), Error> {
+pub fn init_clips_table(connection: &Connection) -> Result<(), Error> {
     // create the clips table if it does not exist
     match connection.execute(
         "CREATE TABLE IF NOT EXISTS clips (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             type INTEGER NOT NULL DEFAULT 0,
             text TEXT NOT NULL,
-            timestamp INTEGER NOT NULL
+            timestamp INTEGER NOT NULL,
+            hash TEXT NOT NULL DEFAULT '',
+            deleted_at INTEGER DEFAULT NULL,
+            preview TEXT NOT NULL DEFAULT '',
+            size INTEGER NOT NULL DEFAULT 0,
+            compressed_text BLOB DEFAULT NULL,
+            copy_count INTEGER NOT NULL DEFAULT 1
         )",
         [],
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::CreateClipsTableErr(err.to_string())),
+    };
+
+    init_clips_hash_index(connection)?;
+    init_clips_deleted_at_index(connection)
+}
+
+/// create the index on the hash column of the clips table if it does not exist
+#[warn(unused_must_use)]
+pub fn init_clips_hash_index(connection: &Connection) -> Result<(), Error> {
+    match connection.execute(
+        "CREATE INDEX IF NOT EXISTS clips_hash_index ON clips (hash)",
+        [],
     ) {
         Ok(_) => Ok(()),
         Err(err) => Err(Error::CreateClipsTableErr(err.to_string())),
     }
 }
 
+/// create the index on the deleted_at column of the clips table if it does not exist
+#[warn(unused_must_use)]
+pub fn init_clips_deleted_at_index(connection: &Connection) -> Result<(), Error> {
+    match connection.execute(
+        "CREATE INDEX IF NOT EXISTS clips_deleted_at_index ON clips (deleted_at)",
+        [],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::CreateClipsTableErr(err.to_string())),
+    }
+}
+
+/// init the full text search table of the clips
+///
+/// this function will
+///     - create the clips_fts table if it does not exist
+///     - create the triggers to keep the clips_fts table in sync with the clips table
+///
+/// the triggers only use plain sql, so the clips can be changed by any connection,
+/// they index the text column, which is only the preview of a compressed clip,
+/// the app indexes the full text of the compressed clips with `index_compressed_text`
+///
+/// the existing clips are not indexed, use `rebuild_clips_fts_table` to do that
+#[warn(unused_must_use)]
+pub fn init_clips_fts_table(connection: &Connection) -> Result<(), Error> {
+    match connection.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS clips_fts USING fts5(
+            text,
+            content = '',
+            contentless_delete = 1
+        );
+        CREATE TRIGGER IF NOT EXISTS clips_fts_insert AFTER INSERT ON clips BEGIN
+            INSERT INTO clips_fts (rowid, text) VALUES (new.id, new.text);
+        END;
+        CREATE TRIGGER IF NOT EXISTS clips_fts_delete AFTER DELETE ON clips BEGIN
+            DELETE FROM clips_fts WHERE rowid = old.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS clips_fts_update AFTER UPDATE OF text, compressed_text ON clips BEGIN
+            DELETE FROM clips_fts WHERE rowid = old.id;
+            INSERT INTO clips_fts (rowid, text) VALUES (new.id, new.text);
+        END;",
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::CreateClipsTableErr(err.to_string())),
+    }
+}
+
+/// drop the clips_fts table and its triggers,
+/// used to replace a clips_fts table created with another definition
+#[warn(unused_must_use)]
+pub fn drop_clips_fts_table(connection: &Connection) -> Result<(), Error> {
+    match connection.execute_batch(
+        "DROP TRIGGER IF EXISTS clips_fts_insert;
+        DROP TRIGGER IF EXISTS clips_fts_delete;
+        DROP TRIGGER IF EXISTS clips_fts_update;
+        DROP TABLE IF EXISTS clips_fts;",
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// replace the preview of a compressed clip in the clips_fts index with its full text,
+/// called after the clip is inserted or its text is changed
+#[warn(unused_must_use)]
+pub fn index_compressed_text(connection: &Connection, id: u64, text: &str) -> Result<(), Error> {
+    match connection.execute("DELETE FROM clips_fts WHERE rowid = ?", [id]) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+    match connection.execute(
+        "INSERT INTO clips_fts (rowid, text) VALUES (?, ?)",
+        rusqlite::params![id, text],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// rebuild the full text search index from the clips table
+///
+/// the contentless table can not use the fts5 rebuild command,
+/// so the index is emptied and the text of every clip is inserted again,
+/// the compressed clips are decompressed one by one
+#[warn(unused_must_use)]
+pub fn rebuild_clips_fts_table(connection: &Connection) -> Result<(), Error> {
+    match connection.execute_batch(
+        "INSERT INTO clips_fts (clips_fts) VALUES ('delete-all');
+        INSERT INTO clips_fts (rowid, text) SELECT id, text FROM clips;",
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    let mut stmt = match connection
+        .prepare("SELECT id, compressed_text FROM clips WHERE compressed_text IS NOT NULL")
+    {
+        Ok(stmt) => stmt,
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+    let rows = match stmt.query_map([], |row| {
+        Ok((row.get::<_, u64>(0)?, row.get::<_, Vec<u8>>(1)?))
+    }) {
+        Ok(rows) => rows,
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+    for row in rows {
+        let (id, compressed_text) = match row {
+            Ok(row) => row,
+            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+        };
+        let text = full_text(id, String::new(), Some(compressed_text))?;
+        index_compressed_text(connection, id, &text)?;
+    }
+
+    Ok(())
+}
+
 /// Get all labels from the labels table
 /// This function is used to get all the labels from the labels table
 /// and return a Vec<String> of the labels
@@ -335,9 +728,9 @@ pub fn get_all_labels(connection: &Connection) -> Result<Vec<String>, Error> {
 /// this function will
 ///   - create the labels table if it does not exist
 ///   - insert the default labels into the labels table
-///   - create the label_base32(label_name) tables for each label
+///   - create the clip_labels table if it does not exist
 #[warn(unused_must_use)]
-fn init_labels_table(connection: &Connection) -> Result<(), Error> {
+pub fn init_labels_table(connection: &Connection) -> Result<(), Error> {
     // create the labels table if it does not exist
     match connection.execute(
         "CREATE TABLE IF NOT EXISTS labels (name TEXT PRIMARY KEY)",
@@ -360,36 +753,162 @@ fn init_labels_table(connection: &Connection) -> Result<(), Error> {
         Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
     };
 
-    // get all the labels from the labels table
-    let labels = get_all_labels(connection)?;
-
-    // create the label_base32(label_name) tables for each label
-    for label in labels {
-        match connection.execute(
-            &format!(
-                "CREATE TABLE IF NOT EXISTS {} (
-                    id INTEGER PRIMARY KEY,
-                    FOREIGN KEY (id) REFERENCES clips (id)
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE
-                )",
-                label_name_to_table_name(&label)
-            ),
-            [],
-        ) {
-            Ok(_) => (),
-            Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
-        };
+    init_clip_labels_table(connection)
+}
+
+/// create the clip_labels table and its indexes if they do not exist
+///
+/// the primary key is used to find the labels of a clip,
+/// and the label index is used to find the clips of a label
+#[warn(unused_must_use)]
+pub fn init_clip_labels_table(connection: &Connection) -> Result<(), Error> {
+    match connection.execute(
+        "CREATE TABLE IF NOT EXISTS clip_labels (
+            clip_id INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            PRIMARY KEY (clip_id, label),
+            FOREIGN KEY (clip_id) REFERENCES clips (id)
+                ON UPDATE CASCADE
+                ON DELETE CASCADE,
+            FOREIGN KEY (label) REFERENCES labels (name)
+                ON UPDATE CASCADE
+                ON DELETE CASCADE
+        )",
+        [],
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    match connection.execute(
+        "CREATE INDEX IF NOT EXISTS clip_labels_label_index ON clip_labels (label, clip_id)",
+        [],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
     }
+}
 
-    Ok(())
+/// create the clip_revisions table and its index if they do not exist
+///
+/// the revisions are deleted with the clip
+#[warn(unused_must_use)]
+pub fn init_clip_revisions_table(connection: &Connection) -> Result<(), Error> {
+    match connection.execute(
+        "CREATE TABLE IF NOT EXISTS clip_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            clip_id INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            FOREIGN KEY (clip_id) REFERENCES clips (id)
+                ON UPDATE CASCADE
+                ON DELETE CASCADE
+        )",
+        [],
+    ) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    match connection.execute(
+        "CREATE INDEX IF NOT EXISTS clip_revisions_clip_id_index ON clip_revisions (clip_id, id)",
+        [],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
 }
 
-pub fn label_name_to_table_name(label_name: &str) -> String {
-    format!(
-        "label_{}",
-        BASE32.encode(label_name.as_bytes()).replace('=', "_")
-    )
+/// create the clip_images table if it does not exist
+///
+/// the image is deleted with the clip
+#[warn(unused_must_use)]
+pub fn init_clip_images_table(connection: &Connection) -> Result<(), Error> {
+    match connection.execute(
+        "CREATE TABLE IF NOT EXISTS clip_images (
+            clip_id INTEGER PRIMARY KEY,
+            data BLOB NOT NULL,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            FOREIGN KEY (clip_id) REFERENCES clips (id)
+                ON UPDATE CASCADE
+                ON DELETE CASCADE
+        )",
+        [],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// create the clip_formats table if it does not exist
+///
+/// the formats are deleted with the clip
+#[warn(unused_must_use)]
+pub fn init_clip_formats_table(connection: &Connection) -> Result<(), Error> {
+    match connection.execute(
+        "CREATE TABLE IF NOT EXISTS clip_formats (
+            clip_id INTEGER NOT NULL,
+            format TEXT NOT NULL,
+            data BLOB NOT NULL,
+            PRIMARY KEY (clip_id, format),
+            FOREIGN KEY (clip_id) REFERENCES clips (id)
+                ON UPDATE CASCADE
+                ON DELETE CASCADE
+        )",
+        [],
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// remove the rows in the clip_labels table
+/// whose clip or label does not exist any more
+///
+/// return the number of rows removed
+#[warn(unused_must_use)]
+pub fn remove_orphan_label_rows(connection: &Connection) -> Result<usize, Error> {
+    match connection.execute(
+        "DELETE FROM clip_labels
+        WHERE clip_id NOT IN (SELECT id FROM clips)
+            OR label NOT IN (SELECT name FROM labels)",
+        [],
+    ) {
+        Ok(res) => Ok(res),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// enable the foreign key constraints of the connection,
+/// so deleting a clip or a label also deletes its rows in the clip_labels table
+///
+/// sqlite does not enforce the foreign keys by default,
+/// and the setting is per connection, so it needs to be set every time the database is opened
+#[warn(unused_must_use)]
+pub fn enable_foreign_keys(connection: &Connection) -> Result<(), Error> {
+    match connection.pragma_update(None, "foreign_keys", true) {
+        Ok(_) => (),
+        Err(err) => return Err(Error::DatabaseWriteErr(err.to_string())),
+    };
+
+    // the pragma is a no-op inside a transaction or if sqlite is built without foreign keys
+    match connection.pragma_query_value(None, "foreign_keys", |row| row.get::<_, bool>(0)) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(Error::DatabaseWriteErr(
+            "failed to enable the foreign keys".to_string(),
+        )),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
+}
+
+/// create a label if it does not exist
+pub fn create_label(connection: &Connection, label: &str) -> Result<(), Error> {
+    match connection.execute("INSERT OR IGNORE INTO labels (name) VALUES (?)", [label]) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DatabaseWriteErr(err.to_string())),
+    }
 }
 
 /// Get all the versions from the database,
@@ -6,9 +6,18 @@ use std::fmt;
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq)]
 pub enum Error {
+    /// failed to back up the database before upgrading it
+    /// the first string is the error message
+    BackupDatabaseErr(String),
     /// clip not found in the database
     /// the first i64 is the id of the clip
     ClipNotFoundErr(i64),
+    /// clip revision not found in the database
+    /// the first u64 is the id of the revision
+    ClipRevisionNotFoundErr(u64),
+    /// failed to compress the text of a large clip
+    /// the first string is the error message from flate2
+    CompressClipTextErr(String),
     /// failed to create the app data dir
     /// the error message is the error message from the std::fs::create_dir_all
     CreateAppDataDirErr(String),
@@ -26,6 +35,12 @@ pub enum Error {
     /// failed to write to database error
     /// the first string is the error message
     DatabaseWriteErr(String),
+    /// the database is saved by a newer version of the app, and can not be opened
+    /// the first u32 is the schema version of the database, the second u32 is the latest schema version supported
+    DatabaseSchemaTooNewErr(u32, u32),
+    /// failed to decompress the text of a large clip
+    /// the first u64 is the id of the clip, the second string is the error message
+    DecompressClipTextErr(u64, String),
     /// delete clip from the database failed
     /// the error message is the error message from the sqlite::execute
     /// the first i64 is the id of the clip, the second string is the error message
@@ -39,9 +54,15 @@ pub enum Error {
     /// the error message is the error message from the sqlite::query_row
     /// the first i64 is the id of the clip, the second string is the error message
     GetClipDataFromDatabaseErr(u64, String),
+    /// failed to compute the statistics of the clips history
+    /// the string is the error message
+    GetClipStatisticsErr(String),
     /// get config file path error
     /// the first string is the error message
     GetConfigFilePathErr(String),
+    /// the search query can not be parsed
+    /// the string is the error message
+    InvalidSearchQueryErr(String),
     /// failed to get version from the database
     /// the error message is the error message from the sqlite::query_row
     GetVersionFromDatabaseErr(String),
@@ -50,6 +71,9 @@ pub enum Error {
     /// failed to read whole ids list from the database
     /// error message from sqlite::query_row
     GetWholeIdsErr(String),
+    /// error occurred when importing data
+    /// the first string is the error message
+    ImportError(String),
     /// failed to insert new clip to the database
     /// the error message is the error message from the sqlite::execute
     /// the first string is the clip data, the second string is the error message
@@ -59,9 +83,16 @@ pub enum Error {
     /// the version is the version that failed to insert
     /// the first string is the version, the second string is the error message
     InsertVersionErr(String, String),
+    /// failed to check or repair the database integrity
+    /// the first string is the error message
+    IntegrityCheckErr(String),
     /// get empty or invalid id from the whole ids list
     /// the first string is the id given by the whole ids list
     InvalidIDFromWholeListErr(Option<i64>),
+    /// invalid operation on the labels,
+    /// for instance creating a label that already exists, or deleting a default label
+    /// the first string is the error message
+    LabelErr(String),
     /// failed to open the database
     /// the error message is the error message from the sqlite::open
     OpenDatabaseErr(String),
@@ -70,6 +101,9 @@ pub enum Error {
     ReadFromSystemClipboardErr(String),
     /// invalid regexp string
     RegexpErr(String),
+    /// failed to restore the database from the backup
+    /// the first string is the path of the backup, the second string is the error message
+    RestoreDatabaseErr(String, String),
     /// serialize config to json error
     /// the first string is the error message
     SerializeConfigToJsonErr(String),
@@ -81,6 +115,10 @@ pub enum Error {
     /// update clips in database failed
     /// the first string is the error message, the second string is the error message from the sqlite::execute
     UpdateClipsInDatabaseErr(String, String),
+    /// failed to upgrade the database, the database is restored from the backup
+    /// the first string is the version saved in the database, the second string is the path of the backup,
+    /// the third string is the error message
+    UpgradeDatabaseErr(String, String, String),
     /// the whole ids list is empty
     WholeListIDSEmptyErr,
     /// failed to write config file to the disk
@@ -114,7 +152,11 @@ impl Error {
             Error::WholeListIDSEmptyErr => "the whole ids list is empty".to_string(),
             Error::InvalidIDFromWholeListErr(id) => format!("get empty or invalid id from the whole ids list, id: {id:?}"),
             Error::GetClipDataFromDatabaseErr(id, err) => format!("get clip data from the database failed, id: {id}, error message: {err}"),
+            Error::GetClipStatisticsErr(err) => format!("failed to get the statistics of the clips, error message: {err}"),
             Error::ClipNotFoundErr(id) => format!("clip not found in the database, id: {id}"),
+            Error::ClipRevisionNotFoundErr(id) => format!("clip revision not found in the database, id: {id}"),
+            Error::CompressClipTextErr(err) => format!("failed to compress the text of the clip, error message: {err}"),
+            Error::DecompressClipTextErr(id, err) => format!("failed to decompress the text of the clip, id: {id}, error message: {err}"),
             Error::DeleteClipFromDatabaseErr(id, err) => format!("delete clip from the database failed, id: {id}, error message: {err}"),
             Error::DatabaseConnectionErr => "the database connection is none".to_string(),
             Error::DatabaseWriteErr(err) => format!("failed to write to database error, error message: {err}"),
@@ -125,9 +167,17 @@ impl Error {
             Error::SerializeConfigToJsonErr(err) => format!("serialize config to json error, error message: {err}"),
             Error::WriteConfigFileErr(err) => format!("failed to write config file to the disk, error message: {err}"),
             Error::UpdateClipsInDatabaseErr(err, err2) => format!("update clips in database failed, error message: {err}, error message from sqlite::execute: {err2}"),
+            Error::DatabaseSchemaTooNewErr(version, supported) => format!("the database is saved by a newer version of the app, schema version: {version}, latest supported schema version: {supported}, please update the app"),
+            Error::InvalidSearchQueryErr(err) => format!("invalid search query, error message: {err}"),
             Error::RegexpErr(err) => format!("invalid regexp string, error message: {err}"),
+            Error::BackupDatabaseErr(err) => format!("failed to back up the database, error message: {err}"),
+            Error::RestoreDatabaseErr(backup, err) => format!("failed to restore the database from the backup {backup}, error message: {err}"),
+            Error::UpgradeDatabaseErr(version, backup, err) => format!("failed to upgrade the database from version {version}, the database is restored from the backup {backup}, error message: {err}"),
             Error::ReadFromSystemClipboardErr(err) => format!("read from system clipboard failed, error message: {err}"),
             Error::ExportError(err) => format!("error occurred when exporting data, error message: {err}"),
+            Error::ImportError(err) => format!("error occurred when importing data, error message: {err}"),
+            Error::IntegrityCheckErr(err) => format!("failed to check the database integrity, error message: {err}"),
+            Error::LabelErr(err) => format!("invalid operation on the labels, error message: {err}"),
             Error::CreatePinnedClipsTableErr(err) => format!("create pinned clips table failed, error message: {err}"),
             Error::GetPinnedClipsErr(err) => format!("failed to get pinned clips from the database, error message: {err}"),
             Error::GetFavouriteClipsErr(err) => format!("failed to get favourite clips from the database, error message: {err}"),
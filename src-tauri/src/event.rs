@@ -4,8 +4,9 @@ use tauri::async_runtime::{Receiver, Sender};
 use tauri::{api::notification::Notification, AppHandle, Manager};
 use tauri_plugin_logging::panic_app;
 
-use crate::clip::clip_data::ClipStateMutex;
+use crate::clip::{clip_data::ClipStateMutex, retention::prune};
 use crate::{
+    backup::scheduled_backup,
     config::ConfigMutex,
     systray::{create_tray_menu, handle_menu_item_click},
 };
@@ -30,6 +31,12 @@ pub enum CopyClipEvent {
     /// this event is sent when the pinned clips changed
     /// should update the pinned clips in the tray menu
     PinnedClipsChangedEvent,
+    /// apply the retention policy in the config,
+    /// delete the oldest clips breaking the limits
+    PruneClipsEvent,
+    /// take the scheduled database backups,
+    /// and delete the backups more than the limits in the config
+    BackupDatabaseEvent,
 }
 
 /// the event sender
@@ -179,6 +186,20 @@ pub async fn event_daemon(mut rx: Receiver<CopyClipEvent>, app: &AppHandle) {
             CopyClipEvent::TrayMenuItemClickEvent(id) => tauri::async_runtime::spawn(async move {
                 handle_menu_item_click(&app, id).await;
             }),
+            // prune clips event
+            CopyClipEvent::PruneClipsEvent => tauri::async_runtime::spawn(async move {
+                let res = prune(&app).await;
+                if let Err(err) = res {
+                    error!("Failed to prune clips, error: {}", err.message());
+                }
+            }),
+            // backup database event
+            CopyClipEvent::BackupDatabaseEvent => tauri::async_runtime::spawn(async move {
+                let res = scheduled_backup(&app).await;
+                if let Err(err) = res {
+                    error!("Failed to back up the database, error: {}", err.message());
+                }
+            }),
             CopyClipEvent::SendNotificationEvent(msg) => tauri::async_runtime::spawn(async move {
                 #[cfg(debug_assertions)]
                 log::debug!("Notification: {}", msg);
@@ -0,0 +1,197 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use clip::Clip;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression, Crc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, error};
+
+use super::for_each_clip;
+
+/// the format version of the archive written by `write_archive`
+///
+/// version 1 is the colon separated base64 format without a manifest,
+/// which is still supported by the import
+pub const ARCHIVE_FORMAT_VERSION: u32 = 2;
+
+/// the first line of a v2 archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// the format version of the archive
+    pub format_version: u32,
+    /// the version of the app that wrote the archive
+    pub app_version: String,
+    /// the unix epoch timestamp in seconds when the archive was written
+    pub exported_at: i64,
+    /// the number of clips in the archive
+    pub clip_count: u64,
+    /// the crc32 of all the clip lines, including the newlines, in lower case hex
+    pub checksum: String,
+    /// the user config
+    pub config: Config,
+    /// the versions saved in the database, in the format of Vec<id,version>
+    pub versions: Vec<(i64, String)>,
+}
+
+/// the checksum used in the manifest
+pub fn checksum_to_string(crc: &Crc) -> String {
+    format!("{:08x}", crc.sum())
+}
+
+/// write the v2 archive to the file
+///
+/// the archive is gzip compressed, and contains
+///     - the manifest in json format, in the first line
+///     - each clip in json format, one clip per line, with the labels and the type
+///
+/// as the manifest contains the number of clips and the checksum,
+/// the clips are first streamed from the database to a temporary file next to the archive,
+/// and then copied into the archive after the manifest
+#[warn(unused_must_use)]
+pub fn write_archive(
+    connection: &Connection,
+    file_path: &str,
+    mut manifest: ArchiveManifest,
+) -> Result<(), error::Error> {
+    let temp_path = format!("{}.part", file_path);
+    let res = write_archive_with_temp_file(connection, file_path, &temp_path, &mut manifest);
+    // the temporary file is not needed any more, whether the export succeeded or not
+    let _ = std::fs::remove_file(&temp_path);
+
+    res
+}
+
+fn write_archive_with_temp_file(
+    connection: &Connection,
+    file_path: &str,
+    temp_path: &str,
+    manifest: &mut ArchiveManifest,
+) -> Result<(), error::Error> {
+    // stream the clips to the temporary file
+    let temp_file = match std::fs::File::create(temp_path) {
+        Ok(file) => file,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
+    let mut temp_file = std::io::BufWriter::new(temp_file);
+    let mut crc = Crc::new();
+    let clip_count = for_each_clip(connection, |clip| {
+        let mut line = match clip.to_json_string() {
+            Ok(line) => line,
+            Err(err) => return Err(error::Error::ExportError(err)),
+        };
+        line.push('\n');
+        crc.update(line.as_bytes());
+        match temp_file.write_all(line.as_bytes()) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(error::Error::ExportError(err.to_string())),
+        }
+    })?;
+    if let Err(err) = temp_file.flush() {
+        return Err(error::Error::ExportError(err.to_string()));
+    }
+    drop(temp_file);
+
+    manifest.clip_count = clip_count;
+    manifest.checksum = checksum_to_string(&crc);
+
+    // write the manifest and the clips to the archive
+    let file = match std::fs::File::create(file_path) {
+        Ok(file) => file,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
+    let mut e = GzEncoder::new(file, Compression::new(9));
+
+    let m_json = match serde_json::to_string(manifest) {
+        Ok(m_json) => m_json,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
+    if let Err(err) = e.write_all(m_json.as_bytes()) {
+        return Err(error::Error::ExportError(err.to_string()));
+    }
+    if let Err(err) = e.write_all("\n".as_bytes()) {
+        return Err(error::Error::ExportError(err.to_string()));
+    }
+
+    let mut temp_file = match std::fs::File::open(temp_path) {
+        Ok(file) => file,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
+    if let Err(err) = std::io::copy(&mut temp_file, &mut e) {
+        return Err(error::Error::ExportError(err.to_string()));
+    }
+
+    let mut file = match e.finish() {
+        Ok(file) => file,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
+    if let Err(err) = file.flush() {
+        return Err(error::Error::ExportError(err.to_string()));
+    }
+    if let Err(err) = file.sync_all() {
+        return Err(error::Error::ExportError(err.to_string()));
+    }
+
+    Ok(())
+}
+
+/// the content of an archive
+pub struct Archive {
+    pub manifest: ArchiveManifest,
+    pub clips: Vec<Clip>,
+}
+
+/// read a v2 archive from the decompressed reader,
+/// the reader should be positioned after the manifest line
+///
+/// the number of clips and the checksum are verified against the manifest
+pub fn read_archive<R: Read>(
+    manifest: ArchiveManifest,
+    reader: BufReader<R>,
+) -> Result<Archive, error::Error> {
+    if manifest.format_version != ARCHIVE_FORMAT_VERSION {
+        return Err(error::Error::ImportError(format!(
+            "unsupported archive format version: {}",
+            manifest.format_version
+        )));
+    }
+
+    let mut crc = Crc::new();
+    let mut clips = Vec::new();
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return Err(error::Error::ImportError(err.to_string())),
+        };
+        crc.update(line.as_bytes());
+        crc.update("\n".as_bytes());
+        let clip: Clip = match serde_json::from_str(&line) {
+            Ok(clip) => clip,
+            Err(err) => return Err(error::Error::ImportError(err.to_string())),
+        };
+        clips.push(clip);
+    }
+
+    if clips.len() as u64 != manifest.clip_count {
+        return Err(error::Error::ImportError(format!(
+            "the archive should contain {} clips, but {} clips found",
+            manifest.clip_count,
+            clips.len()
+        )));
+    }
+    if checksum_to_string(&crc) != manifest.checksum {
+        return Err(error::Error::ImportError(
+            "the checksum of the archive does not match, the archive may be corrupted".to_string(),
+        ));
+    }
+
+    Ok(Archive { manifest, clips })
+}
+
+/// open a gzip compressed export file for reading
+pub fn open_export_file(path: &str) -> Result<BufReader<GzDecoder<std::fs::File>>, error::Error> {
+    match std::fs::File::open(path) {
+        Ok(file) => Ok(BufReader::new(GzDecoder::new(file))),
+        Err(err) => Err(error::Error::ImportError(err.to_string())),
+    }
+}
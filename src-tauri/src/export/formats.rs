@@ -0,0 +1,180 @@
+use std::io::Write;
+
+use clip::Clip;
+use rusqlite::Connection;
+
+use crate::error;
+
+use super::for_each_clip;
+
+/// the formats the clips can be exported to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// the gzip compressed archive, which can be imported back,
+    /// see `archive::write_archive`
+    Archive,
+    /// a json array of clips
+    Json,
+    /// csv with the columns id, type, timestamp, labels, text
+    Csv,
+    /// a markdown document, one section per clip
+    Markdown,
+    /// the text of the clips only
+    Text,
+}
+
+impl ExportFormat {
+    /// the default file name when the user does not give one
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Archive => "copy_clip_data.gz",
+            ExportFormat::Json => "copy_clip_data.json",
+            ExportFormat::Csv => "copy_clip_data.csv",
+            ExportFormat::Markdown => "copy_clip_data.md",
+            ExportFormat::Text => "copy_clip_data.txt",
+        }
+    }
+}
+
+impl TryFrom<&str> for ExportFormat {
+    type Error = error::Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "archive" => Ok(ExportFormat::Archive),
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "markdown" => Ok(ExportFormat::Markdown),
+            "text" => Ok(ExportFormat::Text),
+            _ => Err(error::Error::ExportError(format!(
+                "unknown export format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// convert the io error to the export error
+fn write_err(err: std::io::Error) -> error::Error {
+    error::Error::ExportError(err.to_string())
+}
+
+/// write all the clips as a json array
+pub fn write_json<W: Write>(connection: &Connection, writer: &mut W) -> Result<(), error::Error> {
+    writer.write_all("[".as_bytes()).map_err(write_err)?;
+    let mut first = true;
+    for_each_clip(connection, |clip| {
+        if !first {
+            writer.write_all(",".as_bytes()).map_err(write_err)?;
+        }
+        first = false;
+        let c_json = match clip.to_json_string() {
+            Ok(c_json) => c_json,
+            Err(err) => return Err(error::Error::ExportError(err)),
+        };
+        writer.write_all("\n".as_bytes()).map_err(write_err)?;
+        writer.write_all(c_json.as_bytes()).map_err(write_err)
+    })?;
+    writer.write_all("\n]\n".as_bytes()).map_err(write_err)
+}
+
+/// quote a csv field if it contains a comma, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// write all the clips as csv,
+/// the labels of a clip are separated by ";"
+pub fn write_csv<W: Write>(connection: &Connection, writer: &mut W) -> Result<(), error::Error> {
+    writer
+        .write_all("id,type,timestamp,labels,text\r\n".as_bytes())
+        .map_err(write_err)?;
+    for_each_clip(connection, |clip| {
+        let line = format!(
+            "{},{},{},{},{}\r\n",
+            clip.id,
+            clip.clip_type,
+            clip.timestamp,
+            csv_field(&clip.labels.join(";")),
+            csv_field(&clip.text)
+        );
+        writer.write_all(line.as_bytes()).map_err(write_err)
+    })?;
+
+    Ok(())
+}
+
+/// format the timestamp in the local time zone
+fn format_timestamp(timestamp: i64) -> String {
+    match chrono::DateTime::from_timestamp(timestamp, 0) {
+        Some(time) => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// the markdown section of one clip,
+/// the text is put into a code fence longer than any backtick run in the text
+fn markdown_section(clip: &Clip) -> String {
+    let mut longest_backtick_run = 0;
+    let mut current_run = 0;
+    for c in clip.text.chars() {
+        if c == '`' {
+            current_run += 1;
+            longest_backtick_run = longest_backtick_run.max(current_run);
+        } else {
+            current_run = 0;
+        }
+    }
+    let fence = "`".repeat((longest_backtick_run + 1).max(3));
+
+    let labels = if clip.labels.is_empty() {
+        "-".to_string()
+    } else {
+        clip.labels.join(", ")
+    };
+
+    format!(
+        "## Clip {}\n\n- Type: {}\n- Time: {}\n- Labels: {}\n\n{}\n{}\n{}\n\n",
+        clip.id,
+        clip.clip_type,
+        format_timestamp(clip.timestamp),
+        labels,
+        fence,
+        clip.text,
+        fence
+    )
+}
+
+/// write all the clips as a markdown document
+pub fn write_markdown<W: Write>(
+    connection: &Connection,
+    writer: &mut W,
+) -> Result<(), error::Error> {
+    writer
+        .write_all("# CopyClip Export\n\n".as_bytes())
+        .map_err(write_err)?;
+    for_each_clip(connection, |clip| {
+        writer
+            .write_all(markdown_section(&clip).as_bytes())
+            .map_err(write_err)
+    })?;
+
+    Ok(())
+}
+
+/// write the text of all the clips, separated by an empty line
+pub fn write_text<W: Write>(connection: &Connection, writer: &mut W) -> Result<(), error::Error> {
+    for_each_clip(connection, |clip| {
+        writer.write_all(clip.text.as_bytes()).map_err(write_err)?;
+        writer.write_all("\n\n".as_bytes()).map_err(write_err)
+    })?;
+
+    Ok(())
+}
@@ -1,150 +1,185 @@
-use std::io::Write;
+use std::sync::Arc;
 
 use crate::clip::clip_data::ClipStateMutex;
+use crate::clip::get_system_timestamp;
 use crate::config::ConfigMutex;
 
-use crate::database::get_all_versions;
+use crate::database::{get_all_versions, get_current_version, DatabaseStateMutex};
 use crate::error;
 use crate::event::EventSender;
 
 extern crate directories;
 
-use base64::Engine;
-
+use clip::{Clip, ClipType};
 #[cfg(debug_assertions)]
 use log::debug;
 
 use log::warn;
+use rusqlite::Connection;
 use tauri::{AppHandle, Manager};
 
-use flate2::write::GzEncoder;
-use flate2::Compression;
+use self::{
+    archive::{write_archive, ArchiveManifest, ARCHIVE_FORMAT_VERSION},
+    formats::{write_csv, write_json, write_markdown, write_text, ExportFormat},
+};
 
-/// use to export the use data
-/// the data is the data to export
-///
-/// the data contains three parts,
-/// the first part is the user config encoded in json format, then encoded in base64
-/// the second part is the version json Vec<id,version> in database file data encoded in base64
-/// the third part is the data json Vec<Clip> in database file data encoded in base64
-/// like this:
-///     - config
-///     - :
-///     - versions
-///     - :
-///     - clip_1
-///     - :
-///     - clip_2
-///     - :
-///     - clip_3
-///     - ...
-/// and the three parts are separated by a ":"
+pub mod archive;
+pub mod formats;
+
+/// Iterate over all the clips in the database, ordered by id,
+/// with one streaming query, the labels of each clip are loaded in the same query.
+/// The clips in the trash are not included.
 ///
-/// then the data is compressed using gzip
-/// and then save to user download folder
-#[warn(unused_must_use)]
-async fn export_data(app: &AppHandle, path: String) -> Result<(), error::Error> {
-    // write to file
-    // get the file path
-    let mut file_path = path;
-    file_path.push_str("/copy_clip_data.gz");
-    #[cfg(debug_assertions)]
-    {
-        let d = file_path.to_string();
-        debug!("{}", d);
-    }
+/// Return the number of clips visited.
+pub fn for_each_clip<F>(connection: &Connection, mut f: F) -> Result<u64, error::Error>
+where
+    F: FnMut(Clip) -> Result<(), error::Error>,
+{
+    let mut stmt = match connection.prepare(
+        "SELECT id, type, clip_text(text, compressed_text), timestamp,
+            (SELECT json_group_array(label) FROM clip_labels WHERE clip_labels.clip_id = clips.id) AS labels
+        FROM clips WHERE deleted_at IS NULL ORDER BY id ASC",
+    ) {
+        Ok(stmt) => stmt,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
 
-    let file = std::fs::File::create(file_path);
-    if let Err(e) = file {
-        return Err(error::Error::ExportError(e.to_string()));
+    let mut rows = match stmt.query([]) {
+        Ok(rows) => rows,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
+
+    let mut count = 0;
+    loop {
+        let row = match rows.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(err) => return Err(error::Error::ExportError(err.to_string())),
+        };
+        let clip = match clip_with_labels_from_row(row) {
+            Ok(clip) => clip,
+            Err(err) => return Err(error::Error::ExportError(err.to_string())),
+        };
+        f(clip)?;
+        count += 1;
     }
-    let file = file.unwrap();
 
-    // let mut e = ZlibEncoder::new(file, Compression::default());
-    let mut e = GzEncoder::new(file, Compression::new(9));
+    Ok(count)
+}
 
-    // get config json
-    let config = app.state::<ConfigMutex>();
-    let config = config.config.lock().await;
-    let c_json = config.to_json()?;
-    drop(config);
-    // encode the config json to base64
-    let c_json = base64::engine::general_purpose::STANDARD.encode(c_json.as_bytes());
-    #[cfg(debug_assertions)]
-    debug!("config json: {}", c_json);
-    // write to the encoder
-    let res = e.write_all(c_json.as_bytes());
-    if let Err(e) = res {
-        return Err(error::Error::ExportError(e.to_string()));
-    }
+/// get the clip from a row of the query in `for_each_clip`
+fn clip_with_labels_from_row(row: &rusqlite::Row) -> Result<Clip, rusqlite::Error> {
+    let id: u64 = row.get(0)?;
+    let clip_type: u8 = row.get(1)?;
+    let text: String = row.get(2)?;
+    let timestamp: i64 = row.get(3)?;
+    let labels: String = row.get(4)?;
+    let labels: Vec<String> = match serde_json::from_str(&labels) {
+        Ok(labels) => labels,
+        Err(err) => {
+            return Err(rusqlite::Error::FromSqlConversionFailure(
+                4,
+                rusqlite::types::Type::Text,
+                Box::new(err),
+            ))
+        }
+    };
 
-    // write the separator
-    let res = e.write_all(":".as_bytes());
-    if let Err(e) = res {
-        return Err(error::Error::ExportError(e.to_string()));
-    }
+    Ok(Clip {
+        id,
+        size: text.len() as u64,
+        text: Arc::new(text),
+        timestamp,
+        clip_type: ClipType::from(clip_type),
+        labels,
+        representations: Vec::new(),
+    })
+}
+
+/// export the archive, see `archive::write_archive`,
+/// the manifest contains the user config and the versions saved in the database
+async fn export_archive(app: &AppHandle, file_path: &str) -> Result<(), error::Error> {
+    // get config
+    let config = app.state::<ConfigMutex>();
+    let config = config.config.lock().await.clone();
 
     // get all versions from database
     let versions = get_all_versions(app).await?;
-    // convert to json
-    let v_json = serde_json::to_string(&versions);
-    if let Err(e) = v_json {
-        return Err(error::Error::ExportError(e.to_string()));
-    }
-    let v_json = v_json.unwrap();
-    // encode the json to base64
-    let v_json = base64::engine::general_purpose::STANDARD.encode(v_json.as_bytes());
-    // write to the encoder
-    let res = e.write_all(v_json.as_bytes());
-    if let Err(e) = res {
-        return Err(error::Error::ExportError(e.to_string()));
-    }
 
-    // write the separator
-    let res = e.write_all(":".as_bytes());
-    if let Err(e) = res {
-        return Err(error::Error::ExportError(e.to_string()));
-    }
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        app_version: get_current_version(app)?,
+        exported_at: get_system_timestamp(),
+        // the clip count and the checksum are filled when writing the clips
+        clip_count: 0,
+        checksum: String::new(),
+        config,
+        versions,
+    };
 
-    // the clips
+    // lock the clip state, so no new clip is created during the export
     let clip_data = app.state::<ClipStateMutex>();
     let clip_data = clip_data.clip_state.lock().await;
-    let clips_len = clip_data.get_total_number_of_clip(app).await?;
-    for i in 0..clips_len {
-        let id = clip_data.get_id_with_pos(app, i).await?;
-        let clip = clip_data.get_clip(app, id).await?;
-        if clip.is_none() {
-            continue;
-        }
-        let clip = clip.unwrap();
-        let c_json = clip.to_json_string();
-        if let Err(e) = c_json {
-            return Err(error::Error::ExportError(e.to_string()));
-        }
-        let c_json = c_json.unwrap();
-        let c_json = base64::engine::general_purpose::STANDARD.encode(c_json.as_bytes());
-        let res = e.write_all(c_json.as_bytes());
-        if let Err(e) = res {
-            return Err(error::Error::ExportError(e.to_string()));
-        }
-        let res = e.write_all(":".as_bytes());
-        if let Err(e) = res {
-            return Err(error::Error::ExportError(e.to_string()));
-        }
-    }
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+
+    write_archive(&db_connection, file_path, manifest)?;
+
+    drop(db_connection);
     drop(clip_data);
 
-    let res = e.finish();
-    if let Err(e) = res {
-        return Err(error::Error::ExportError(e.to_string()));
-    }
-    let mut file = res.unwrap();
+    Ok(())
+}
 
-    let res = file.flush();
-    if let Err(err) = res {
-        return Err(error::Error::ExportError(err.to_string()));
+/// use to export the use data
+///
+/// the archive format can be imported back,
+/// the other formats are meant to be read by other tools
+#[warn(unused_must_use)]
+async fn export_data(
+    app: &AppHandle,
+    file_path: &str,
+    format: ExportFormat,
+) -> Result<(), error::Error> {
+    #[cfg(debug_assertions)]
+    debug!("export data to {} in {:?} format", file_path, format);
+
+    if format == ExportFormat::Archive {
+        export_archive(app, file_path).await?;
+
+        #[cfg(debug_assertions)]
+        debug!("export data success");
+
+        return Ok(());
     }
 
+    let file = match std::fs::File::create(file_path) {
+        Ok(file) => file,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
+    let mut writer = std::io::BufWriter::new(file);
+
+    // lock the clip state, so no new clip is created during the export
+    let clip_data = app.state::<ClipStateMutex>();
+    let clip_data = clip_data.clip_state.lock().await;
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let db_connection = db_connection.database_connection.lock().await;
+
+    match format {
+        ExportFormat::Json => write_json(&db_connection, &mut writer)?,
+        ExportFormat::Csv => write_csv(&db_connection, &mut writer)?,
+        ExportFormat::Markdown => write_markdown(&db_connection, &mut writer)?,
+        ExportFormat::Text => write_text(&db_connection, &mut writer)?,
+        ExportFormat::Archive => unreachable!(),
+    };
+
+    drop(db_connection);
+    drop(clip_data);
+
+    let file = match writer.into_inner() {
+        Ok(file) => file,
+        Err(err) => return Err(error::Error::ExportError(err.to_string())),
+    };
     let res = file.sync_all();
     if let Err(err) = res {
         return Err(error::Error::ExportError(err.to_string()));
@@ -156,13 +191,32 @@ async fn export_data(app: &AppHandle, path: String) -> Result<(), error::Error>
     Ok(())
 }
 
-#[tauri::command]
-pub async fn export_data_invoke(
-    app: tauri::AppHandle,
-    event_sender: tauri::State<'_, EventSender>,
-) -> Result<(), error::Error> {
-    // save to user download dir
-    // get the download dir
+/// get the file to export to
+///
+/// if the path is None or empty, export to the user download dir,
+/// if the path is a dir, export to the default file name of the format in the dir
+fn resolve_export_path(path: Option<String>, format: ExportFormat) -> Result<String, error::Error> {
+    let path = match path {
+        Some(path) if !path.is_empty() => std::path::PathBuf::from(path),
+        _ => std::path::PathBuf::from(get_download_dir()?),
+    };
+    let path = if path.is_dir() {
+        path.join(format.default_file_name())
+    } else {
+        path
+    };
+
+    match path.to_str() {
+        Some(path) => Ok(path.to_string()),
+        None => Err(error::Error::ExportError(
+            "the export path is not valid utf-8".to_string(),
+        )),
+    }
+}
+
+/// get the user download dir,
+/// which is the default place to save and load the exported data
+pub fn get_download_dir() -> Result<String, error::Error> {
     let path = directories::UserDirs::new();
     if path.is_none() {
         return Err(error::Error::ExportError(
@@ -183,18 +237,39 @@ pub async fn export_data_invoke(
             "can not get user download dir".to_string(),
         ));
     }
-    let path = path.unwrap();
-    let path = path.to_string();
-    let res = export_data(&app, path).await;
+
+    Ok(path.unwrap().to_string())
+}
+
+/// export the user data
+///
+/// input: {
+///     path: Option<String>, the file or the dir to export to, default to the user download dir
+///     format: Option<String>, one of archive, json, csv, markdown, text, default to archive
+/// }
+#[tauri::command]
+pub async fn export_data_invoke(
+    app: tauri::AppHandle,
+    event_sender: tauri::State<'_, EventSender>,
+    path: Option<String>,
+    format: Option<String>,
+) -> Result<(), error::Error> {
+    let format = match format {
+        Some(format) => ExportFormat::try_from(format.as_str())?,
+        None => ExportFormat::Archive,
+    };
+    let file_path = resolve_export_path(path, format)?;
+    let res = export_data(&app, &file_path, format).await;
     if let Err(err) = res {
         warn!("{}", err);
         return Err(err);
     }
 
     event_sender
-        .send(crate::event::CopyClipEvent::SendNotificationEvent(
-            "Export data successful.".to_string(),
-        ))
+        .send(crate::event::CopyClipEvent::SendNotificationEvent(format!(
+            "Export data successful, saved to {}.",
+            file_path
+        )))
         .await;
 
     Ok(())
@@ -0,0 +1,289 @@
+use std::io::{BufRead, Read};
+
+use clip::{Clip, ClipType};
+use log::{debug, warn};
+use rusqlite::Connection;
+use rust_i18n::set_locale;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    clip::{clip_data::ClipStateMutex, text::StoredText},
+    config::{Config, ConfigMutex},
+    database::{clip_hash, create_label, index_compressed_text, DatabaseStateMutex},
+    error,
+    event::{CopyClipEvent, EventSender},
+    export::{
+        archive::{open_export_file, read_archive, ArchiveManifest},
+        formats::ExportFormat,
+        get_download_dir,
+    },
+};
+
+mod v1;
+
+/// the summary of an import,
+/// returned to the frontend after the import is finished
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// the number of clips added to the database
+    pub added: u64,
+    /// the number of clips skipped,
+    /// because the same clip is already in the database
+    pub skipped: u64,
+}
+
+/// the data decoded from an export file
+pub struct ExportedData {
+    /// the user config,
+    /// None if the config in the export file can not be parsed
+    pub config: Option<Config>,
+    /// the versions saved in the exported database
+    pub versions: Vec<(i64, String)>,
+    /// the exported clips, in the same order as in the exported database
+    pub clips: Vec<Clip>,
+}
+
+/// read the file produced by `export::export_data`
+///
+/// the format is detected from the content,
+/// a v2 archive starts with the json manifest,
+/// while a v1 export starts with the base64 encoded config
+fn read_export_file(path: &str) -> Result<ExportedData, error::Error> {
+    let mut reader = open_export_file(path)?;
+
+    let mut first_line = String::new();
+    if let Err(err) = reader.read_line(&mut first_line) {
+        return Err(error::Error::ImportError(err.to_string()));
+    }
+
+    if !first_line.starts_with('{') {
+        // v1 export, there is no newline in the whole file
+        let mut content = first_line;
+        if let Err(err) = reader.read_to_string(&mut content) {
+            return Err(error::Error::ImportError(err.to_string()));
+        }
+        return v1::parse(&content);
+    }
+
+    let manifest: ArchiveManifest = match serde_json::from_str(&first_line) {
+        Ok(manifest) => manifest,
+        Err(err) => return Err(error::Error::ImportError(err.to_string())),
+    };
+    debug!(
+        "import archive written by version {} at {}",
+        manifest.app_version, manifest.exported_at
+    );
+    let archive = read_archive(manifest, reader)?;
+
+    Ok(ExportedData {
+        config: Some(archive.manifest.config),
+        versions: archive.manifest.versions,
+        clips: archive.clips,
+    })
+}
+
+/// insert the clip and its labels into the database
+///
+/// the clip will get a new id, as the old id may be used by another clip,
+/// a clip with the same type, text and timestamp is considered to be already imported,
+/// the indexed hash is used to find the clips with the same text
+///
+/// the image clips are skipped, as the export file only has their description,
+/// the html and rtf clips are imported as plain text, as the export file only has their plain text
+///
+/// the texts larger than the compress threshold are compressed
+///
+/// return false if the clip is skipped
+fn import_clip(
+    connection: &Connection,
+    clip: &Clip,
+    compress_threshold: u64,
+) -> Result<bool, error::Error> {
+    let clip_type: u8 = match clip.clip_type {
+        ClipType::Image => return Ok(false),
+        ClipType::Html | ClipType::Rtf => ClipType::Text.into(),
+        clip_type => clip_type.into(),
+    };
+    let hash = clip_hash(&clip.text);
+
+    match connection.query_row(
+        "SELECT id FROM clips
+        WHERE hash = ? AND clip_text(text, compressed_text) = ? AND type = ? AND timestamp = ?",
+        rusqlite::params![hash, clip.text.as_str(), clip_type, clip.timestamp],
+        |_| Ok(()),
+    ) {
+        Ok(_) => return Ok(false),
+        Err(rusqlite::Error::QueryReturnedNoRows) => (),
+        Err(err) => return Err(error::Error::ImportError(err.to_string())),
+    };
+
+    let stored_text = StoredText::new(&clip.text, compress_threshold)?;
+    let id: u64 = match connection.query_row(
+        "INSERT INTO clips (type, text, timestamp, hash, preview, size, compressed_text)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING id",
+        rusqlite::params![
+            clip_type,
+            stored_text.text,
+            clip.timestamp,
+            hash,
+            stored_text.preview,
+            stored_text.size,
+            stored_text.compressed_text
+        ],
+        |row| row.get(0),
+    ) {
+        Ok(id) => id,
+        Err(err) => {
+            return Err(error::Error::InsertClipIntoDatabaseErr(
+                (*clip.text).clone(),
+                err.to_string(),
+            ))
+        }
+    };
+    if stored_text.compressed_text.is_some() {
+        index_compressed_text(connection, id, &clip.text)?;
+    }
+
+    for label in clip.labels.iter() {
+        create_label(connection, label)?;
+        match connection.execute(
+            "INSERT OR IGNORE INTO clip_labels (clip_id, label) VALUES (?, ?)",
+            rusqlite::params![id, label],
+        ) {
+            Ok(_) => (),
+            Err(err) => return Err(error::Error::ImportError(err.to_string())),
+        };
+    }
+
+    Ok(true)
+}
+
+/// apply the config in the export file to the app,
+/// and save it to the config file
+async fn apply_config(app: &AppHandle, config: Option<Config>) -> Result<(), error::Error> {
+    let mut config = match config {
+        Some(config) => config,
+        None => {
+            return Err(error::Error::ImportError(
+                "the config in the export file is invalid".to_string(),
+            ))
+        }
+    };
+    config.clip_per_page = config.clip_per_page.clamp(1, 50);
+    set_locale(&config.language);
+
+    let config_mutex = app.state::<ConfigMutex>();
+    let mut config_mutex = config_mutex.config.lock().await;
+    *config_mutex = config;
+    drop(config_mutex);
+
+    let event_sender = app.state::<EventSender>();
+    event_sender.send(CopyClipEvent::SaveConfigEvent).await;
+
+    Ok(())
+}
+
+/// import the data exported by `export::export_data`,
+/// both the v1 export and the v2 archive are supported
+///
+/// all the clips are inserted in one transaction,
+/// so either all the clips are imported, or none of them
+#[warn(unused_must_use)]
+async fn import_data(
+    app: &AppHandle,
+    path: &str,
+    apply_config_from_file: bool,
+) -> Result<ImportReport, error::Error> {
+    let data = read_export_file(path)?;
+    debug!(
+        "import {} clips, exported database versions: {:?}",
+        data.clips.len(),
+        data.versions
+    );
+
+    let config = app.state::<ConfigMutex>();
+    let config = config.config.lock().await;
+    let compress_threshold = config.compress_threshold;
+    drop(config);
+
+    // lock the clip state, so no new clip is created during the import
+    let clip_state = app.state::<ClipStateMutex>();
+    let clip_state = clip_state.clip_state.lock().await;
+
+    let db_connection = app.state::<DatabaseStateMutex>();
+    let mut db_connection = db_connection.database_connection.lock().await;
+    let transaction = match db_connection.transaction() {
+        Ok(transaction) => transaction,
+        Err(err) => return Err(error::Error::ImportError(err.to_string())),
+    };
+
+    let mut report = ImportReport::default();
+    for clip in data.clips.iter() {
+        if import_clip(&transaction, clip, compress_threshold)? {
+            report.added += 1;
+        } else {
+            report.skipped += 1;
+        }
+    }
+
+    if let Err(err) = transaction.commit() {
+        return Err(error::Error::ImportError(err.to_string()));
+    }
+    drop(db_connection);
+    drop(clip_state);
+
+    if apply_config_from_file {
+        apply_config(app, data.config).await?;
+    }
+
+    Ok(report)
+}
+
+/// import the data exported by `export_data_invoke`
+///
+/// input: {
+///     path: Option<String>, the export file, default to copy_clip_data.gz in the user download dir
+///     applyconfig: bool, whether to apply the config saved in the export file
+/// }
+///
+/// output: {
+///     added: u64,
+///     skipped: u64,
+/// }
+#[tauri::command]
+pub async fn import_data_invoke(
+    app: AppHandle,
+    event_sender: tauri::State<'_, EventSender>,
+    path: Option<String>,
+    applyconfig: bool,
+) -> Result<ImportReport, error::Error> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let mut path = get_download_dir()?;
+            path.push('/');
+            path.push_str(ExportFormat::Archive.default_file_name());
+            path
+        }
+    };
+
+    let report = match import_data(&app, &path, applyconfig).await {
+        Ok(report) => report,
+        Err(err) => {
+            warn!("{}", err);
+            return Err(err);
+        }
+    };
+
+    event_sender.send(CopyClipEvent::RebuildTrayMenuEvent).await;
+    event_sender
+        .send(CopyClipEvent::SendNotificationEvent(format!(
+            "Import data successful, {} clips added, {} clips skipped.",
+            report.added, report.skipped
+        )))
+        .await;
+
+    Ok(report)
+}
@@ -0,0 +1,88 @@
+use base64::Engine;
+use clip::Clip;
+use log::warn;
+
+use crate::{config::Config, error};
+
+use super::ExportedData;
+
+/// decode a base64 part of the export file to a utf-8 string
+fn decode_base64_part(part: &str) -> Result<String, error::Error> {
+    let bytes = match base64::engine::general_purpose::STANDARD.decode(part) {
+        Ok(bytes) => bytes,
+        Err(err) => return Err(error::Error::ImportError(err.to_string())),
+    };
+
+    match String::from_utf8(bytes) {
+        Ok(s) => Ok(s),
+        Err(err) => Err(error::Error::ImportError(err.to_string())),
+    }
+}
+
+/// parse the decompressed content of a v1 export
+///
+/// the content is
+///     - config
+///     - :
+///     - versions
+///     - :
+///     - clip_1
+///     - :
+///     - clip_2
+///     - :
+///     - ...
+/// each part is encoded in base64
+pub fn parse(content: &str) -> Result<ExportedData, error::Error> {
+    let mut parts = content.trim_end().split(':');
+
+    // the config part
+    let config = match parts.next() {
+        Some(part) => decode_base64_part(part)?,
+        None => {
+            return Err(error::Error::ImportError(
+                "the config part is missing".to_string(),
+            ))
+        }
+    };
+    let config: Option<Config> = match serde_json::from_str(&config) {
+        Ok(config) => Some(config),
+        Err(err) => {
+            warn!("can not parse the config in the export file: {}", err);
+            None
+        }
+    };
+
+    // the versions part
+    let versions = match parts.next() {
+        Some(part) => decode_base64_part(part)?,
+        None => {
+            return Err(error::Error::ImportError(
+                "the versions part is missing".to_string(),
+            ))
+        }
+    };
+    let versions: Vec<(i64, String)> = match serde_json::from_str(&versions) {
+        Ok(versions) => versions,
+        Err(err) => return Err(error::Error::ImportError(err.to_string())),
+    };
+
+    // the clips part, the data end with a separator, so the last part is empty
+    let mut clips = Vec::new();
+    for part in parts {
+        if part.is_empty() {
+            continue;
+        }
+        let c_json = decode_base64_part(part)?;
+        let clip: Clip = match serde_json::from_str(&c_json) {
+            Ok(clip) => clip,
+            Err(err) => return Err(error::Error::ImportError(err.to_string())),
+        };
+        clips.push(clip);
+    }
+
+    Ok(ExportedData {
+        config,
+        versions,
+        clips,
+    })
+}
@@ -8,6 +8,7 @@
 )]
 */
 
+pub mod backup;
 pub mod backward;
 pub mod clip;
 pub mod config;
@@ -15,6 +16,7 @@ pub mod database;
 pub mod error;
 pub mod event;
 pub mod export;
+pub mod import;
 pub mod systray;
 
 #[macro_use]
@@ -2,16 +2,17 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use copy_clip::{
-    clip::{self, clip_data::ClipStateMutex},
+    backup,
+    clip::{self, clip_data::ClipStateMutex, format::ClipboardWriterMutex},
     config::{self, Config, ConfigMutex},
-    database::{init_database_connection, DatabaseStateMutex},
+    database::{self, init_database_connection, DatabaseStateMutex},
     event::{event_daemon, event_sender, CopyClipEvent, EventSender},
-    export,
+    export, import,
     systray::handle_tray_event,
 };
 use log::{error, info};
 use rust_i18n::set_locale;
-use tauri::{async_runtime::Mutex, Manager, SystemTray};
+use tauri::{api::notification::Notification, async_runtime::Mutex, Manager, SystemTray};
 use tauri_plugin_logging::panic_app;
 
 const EVENT_CHANNEL_SIZE: usize = 1000;
@@ -26,6 +27,7 @@ fn main() {
         })
         .manage(ClipStateMutex::default())
         .manage(DatabaseStateMutex::default())
+        .manage(ClipboardWriterMutex::default())
         .setup(|app| {
             // set up the database connection and create the table
             // will also init the clip data state
@@ -34,6 +36,14 @@ fn main() {
                 Ok(connection) => connection,
                 Err(err) => {
                     error!("failed to init database connection");
+                    // the event daemon is not running yet, send the notification directly
+                    let res = Notification::new(&app.config().tauri.bundle.identifier)
+                        .title("Copy Clip failed to start")
+                        .body(err.message())
+                        .notify(&app.handle());
+                    if let Err(err) = res {
+                        error!("failed to send notification, error: {}", err);
+                    }
                     return Err(err.to_string().into());
                 }
             };
@@ -88,6 +98,12 @@ fn main() {
                 clip::monitor::monitor_clip_board(&app_handle).await;
             });
 
+            // apply the retention policy periodically
+            clip::retention::start_prune_timer(&app.handle());
+
+            // take the scheduled database backups periodically
+            backup::start_backup_timer(&app.handle());
+
             // initial the tray
             let app_handle = &app.handle();
             event_sender(app_handle, CopyClipEvent::RebuildTrayMenuEvent);
@@ -114,14 +130,57 @@ fn main() {
             config::command::set_language,
             config::command::get_auto_delete_duplicate_clip,
             config::command::set_auto_delete_duplicate_clip,
+            config::command::get_max_clip_count,
+            config::command::set_max_clip_count,
+            config::command::get_max_clip_age_days,
+            config::command::set_max_clip_age_days,
+            config::command::get_max_total_text_bytes,
+            config::command::set_max_total_text_bytes,
+            config::command::get_retention_exempt_labels,
+            config::command::set_retention_exempt_labels,
+            config::command::get_trash_auto_empty_days,
+            config::command::set_trash_auto_empty_days,
+            config::command::get_backup_keep_daily,
+            config::command::set_backup_keep_daily,
+            config::command::get_backup_keep_weekly,
+            config::command::set_backup_keep_weekly,
+            config::command::get_max_clip_size,
+            config::command::set_max_clip_size,
+            config::command::get_large_clip_policy,
+            config::command::set_large_clip_policy,
+            config::command::get_compress_threshold,
+            config::command::set_compress_threshold,
+            database::integrity::check_database,
+            backup::list_backups,
+            backup::restore_backup,
             export::export_data_invoke,
+            import::import_data_invoke,
             clip::switch_pinned_status,
             clip::copy_clip_to_clipboard,
             clip::delete_clip_from_database,
+            clip::trash::get_trash_clips,
+            clip::trash::restore_clip_from_trash,
+            clip::trash::empty_trash_invoke,
+            clip::undo::undo_last_action,
+            clip::revision::edit_clip,
+            clip::revision::get_clip_revisions,
+            clip::revision::restore_clip_revision,
+            clip::image::get_clip_image_data,
+            clip::file::get_clip_files,
+            clip::text::get_full_clip_text,
             clip::change_favourite_clip,
             clip::search::search_clips,
             clip::search::get_max_id,
+            clip::search::search_duplicate_clips,
+            clip::statistics::get_clip_statistics,
             clip::id_is_pinned,
+            clip::label::get_all_labels,
+            clip::label::get_clip_labels,
+            clip::label::create_label,
+            clip::label::rename_label,
+            clip::label::delete_label,
+            clip::label::add_label_to_clip,
+            clip::label::remove_label_from_clip,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -33,6 +33,7 @@ pub fn create_tray(
 /// - prev page
 /// - next page
 /// - first page
+/// - undo
 /// - preferences
 /// - search
 /// - quit
@@ -54,6 +55,7 @@ pub fn create_tray_menu(
     let next_page = CustomMenuItem::new("next_page".to_string(), t!("tray_menu.next_page"))
         .accelerator("CommandOrControl+D");
     let first_page = CustomMenuItem::new("first_page".to_string(), t!("tray_menu.first_page"));
+    let undo = CustomMenuItem::new("undo".to_string(), t!("tray_menu.undo"));
 
     let preferences = CustomMenuItem::new("preferences".to_string(), t!("tray_menu.preferences"));
     let search = CustomMenuItem::new("search".to_string(), t!("tray_menu.search"));
@@ -100,6 +102,7 @@ pub fn create_tray_menu(
         .add_item(next_page)
         .add_item(first_page)
         .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(undo)
         .add_item(preferences)
         .add_item(search)
         .add_item(pause)
@@ -125,6 +128,7 @@ pub fn create_tray_menu(
     let next_page = CustomMenuItem::new("next_page".to_string(), t!("tray_menu.next_page"))
         .accelerator("CommandOrControl+D");
     let first_page = CustomMenuItem::new("first_page".to_string(), t!("tray_menu.first_page"));
+    let undo = CustomMenuItem::new("undo".to_string(), t!("tray_menu.undo"));
 
     let preferences = CustomMenuItem::new("preferences".to_string(), t!("tray_menu.preferences"));
     let search = CustomMenuItem::new("search".to_string(), t!("tray_menu.search"));
@@ -143,6 +147,7 @@ pub fn create_tray_menu(
         .add_item(pause)
         .add_item(search)
         .add_item(preferences)
+        .add_item(undo)
         .add_native_item(SystemTrayMenuItem::Separator)
         .add_item(first_page)
         .add_item(next_page)
@@ -201,6 +206,7 @@ pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
 /// - next_page
 /// - prev_page
 /// - first_page
+/// - undo
 /// - tray_clip_num
 pub async fn handle_menu_item_click(app: &AppHandle, id: String) {
     match id.as_str() {
@@ -265,6 +271,26 @@ pub async fn handle_menu_item_click(app: &AppHandle, id: String) {
                 error!("Failed to send event, error: {}", err);
             }
         }
+        "undo" => {
+            debug!("Undo clicked");
+            let clip_data = app.state::<ClipStateMutex>();
+            let mut clip_data = clip_data.clip_state.lock().await;
+            let res = clip_data.undo_last_action(app).await;
+            drop(clip_data);
+            let message = match res {
+                Ok(Some(message)) => message,
+                Ok(None) => "Nothing to undo.".to_string(),
+                Err(e) => {
+                    warn!("Failed to undo the last action: {}", e);
+                    e.message()
+                }
+            };
+
+            let event_sender = app.state::<EventSender>();
+            event_sender
+                .send(CopyClipEvent::SendNotificationEvent(message))
+                .await;
+        }
         "preferences" => {
             debug!("Preferences clicked, Opening preferences window");
             // open the preferences window
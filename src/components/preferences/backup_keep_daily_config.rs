@@ -0,0 +1,15 @@
+use yew::{function_component, html, Html};
+
+use super::int_config_template::IntConfigTemplate;
+
+#[function_component(BackupKeepDailyConfig)]
+pub fn backup_keep_daily_config() -> Html {
+    html! {
+        <IntConfigTemplate
+            label={"preferences.backup_keep_daily"}
+            default_value=7
+            set_value_invoke={"set_backup_keep_daily"}
+            get_value_invoke={"get_backup_keep_daily"}
+        />
+    }
+}
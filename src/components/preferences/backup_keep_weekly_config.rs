@@ -0,0 +1,15 @@
+use yew::{function_component, html, Html};
+
+use super::int_config_template::IntConfigTemplate;
+
+#[function_component(BackupKeepWeeklyConfig)]
+pub fn backup_keep_weekly_config() -> Html {
+    html! {
+        <IntConfigTemplate
+            label={"preferences.backup_keep_weekly"}
+            default_value=4
+            set_value_invoke={"set_backup_keep_weekly"}
+            get_value_invoke={"get_backup_keep_weekly"}
+        />
+    }
+}
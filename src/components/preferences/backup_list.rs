@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Event, HtmlInputElement};
+/// the list of the database backups, and the button used to restore one of them
+use yew::{
+    function_component, html, use_effect_with, use_state, Callback, Html, TargetCast,
+    UseStateHandle,
+};
+
+use crate::invoke::invoke;
+
+/// the backup returned by list_backups
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct BackupInfo {
+    name: String,
+    /// one of daily, weekly, upgrade, restore
+    kind: String,
+    /// unix time epoch in seconds
+    timestamp: i64,
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct RestoreBackupArgs {
+    name: String,
+}
+
+/// get the backups from the backend, the newest first
+async fn list_backups() -> Vec<BackupInfo> {
+    let args = to_value(&()).unwrap();
+    let res = invoke("list_backups", args).await;
+    serde_wasm_bindgen::from_value::<Vec<BackupInfo>>(res).unwrap_or_default()
+}
+
+/// the text shown in the drop list
+fn backup_display(backup: &BackupInfo) -> String {
+    let time = match chrono::DateTime::from_timestamp(backup.timestamp, 0) {
+        Some(time) => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => backup.timestamp.to_string(),
+    };
+    let kind = match backup.kind.as_str() {
+        "daily" => t!("backup.daily"),
+        "weekly" => t!("backup.weekly"),
+        "upgrade" => t!("backup.upgrade"),
+        _ => t!("backup.restore"),
+    };
+    format!("{} - {} ({} KB)", time, kind, backup.size / 1024)
+}
+
+#[function_component(BackupList)]
+pub fn backup_list() -> Html {
+    let backups: UseStateHandle<Vec<BackupInfo>> = use_state(Vec::new);
+    let selected: UseStateHandle<String> = use_state(String::new);
+
+    let backups_1 = backups.clone();
+    let selected_1 = selected.clone();
+    use_effect_with((), move |_| {
+        spawn_local(async move {
+            let res = list_backups().await;
+            if let Some(backup) = res.first() {
+                selected_1.set(backup.name.clone());
+            }
+            backups_1.set(res);
+        });
+    });
+
+    let selected_1 = selected.clone();
+    let backup_on_change = Callback::from(move |event: Event| {
+        let value = event.target_unchecked_into::<HtmlInputElement>().value();
+        selected_1.set(value);
+    });
+
+    let backups_1 = backups.clone();
+    let selected_1 = selected.clone();
+    let restore_button_on_click = Callback::from(move |_| {
+        let name = (*selected_1).clone();
+        if name.is_empty() {
+            return;
+        }
+        let backups_1 = backups_1.clone();
+        spawn_local(async move {
+            let args = to_value(&RestoreBackupArgs { name }).unwrap();
+            invoke("restore_backup", args).await;
+            // a backup of the replaced database is added
+            backups_1.set(list_backups().await);
+        });
+    });
+
+    html! (
+        <div class="flex flex-col">
+            <div class="flex flex-row my-2 justify-between">
+                <label class="text-xl">
+                    {t!("backup.backups")}
+                </label>
+                // backups drop list
+                <select
+                    class="border border-gray-200 rounded-md p-2 text-lg dark:text-black"
+                    onchange={backup_on_change}
+                >
+                    {
+                        backups.iter().map(|backup| {
+                            html! {
+                                <option
+                                    value={backup.name.clone()}
+                                    selected={*selected == backup.name}
+                                >
+                                    {backup_display(backup)}
+                                </option>
+                            }
+                        }).collect::<Html>()
+                    }
+                </select>
+            </div>
+
+            // restore button
+            <button
+                class="search-button bg-black my-2"
+                onclick={restore_button_on_click}
+            >
+                <span
+                    class="dark:bg-white dark:text-black text-white"
+                > {t!("backup.restore_button")} </span>
+            </button>
+        </div>
+    )
+}
@@ -0,0 +1,15 @@
+use yew::{function_component, html, Html};
+
+use super::int_config_template::IntConfigTemplate;
+
+#[function_component(CompressThresholdConfig)]
+pub fn compress_threshold_config() -> Html {
+    html! {
+        <IntConfigTemplate
+            label={"preferences.compress_threshold"}
+            default_value=65536
+            set_value_invoke={"set_compress_threshold"}
+            get_value_invoke={"get_compress_threshold"}
+        />
+    }
+}
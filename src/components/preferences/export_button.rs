@@ -1,27 +1,93 @@
+use serde::Serialize;
 use serde_wasm_bindgen::to_value;
 use wasm_bindgen_futures::spawn_local;
+use web_sys::{Event, HtmlInputElement};
 /// the button used to export the user data
-use yew::{function_component, html, Callback, Html};
+use yew::{function_component, html, use_state, Callback, Html, TargetCast, UseStateHandle};
 
 use crate::invoke::invoke;
+
+#[derive(Serialize)]
+struct ExportDataArgs {
+    /// the file or the directory to export to,
+    /// None means the user download dir
+    path: Option<String>,
+    /// one of archive, json, csv, markdown, text
+    format: Option<String>,
+}
+
 #[function_component(ExportButton)]
 pub fn export_button() -> Html {
-    let export_button_on_click = Callback::from(|_| {
+    let format: UseStateHandle<String> = use_state(|| "archive".to_string());
+    let path: UseStateHandle<String> = use_state(String::new);
+
+    let format_1 = format.clone();
+    let format_on_change = Callback::from(move |event: Event| {
+        let value = event.target_unchecked_into::<HtmlInputElement>().value();
+        format_1.set(value);
+    });
+
+    let path_1 = path.clone();
+    let path_on_change = Callback::from(move |event: Event| {
+        let value = event.target_unchecked_into::<HtmlInputElement>().value();
+        path_1.set(value);
+    });
+
+    let format_1 = format.clone();
+    let path_1 = path.clone();
+    let export_button_on_click = Callback::from(move |_| {
+        let path = path_1.trim().to_string();
+        let args = ExportDataArgs {
+            path: if path.is_empty() { None } else { Some(path) },
+            format: Some((*format_1).clone()),
+        };
         spawn_local(async move {
-            let args = to_value(&()).unwrap();
+            let args = to_value(&args).unwrap();
             invoke("export_data_invoke", args).await;
         });
     });
 
     html! (
-        // search button
-        <button
-            class="search-button bg-black my-2"
-            onclick={export_button_on_click}
-        >
-            <span
-                class="dark:bg-white dark:text-black text-white"
-            > {t!("export.export_button")} </span>
-        </button>
+        <div class="flex flex-col">
+            <div class="flex flex-row my-2 justify-between">
+                <label class="text-xl">
+                    {t!("export.format")}
+                </label>
+                // export format drop list
+                <select
+                    class="border border-gray-200 rounded-md p-2 text-lg dark:text-black"
+                    onchange={format_on_change}
+                >
+                    <option value="archive" selected={*format == "archive"}>{t!("export.archive")}</option>
+                    <option value="json" selected={*format == "json"}>{t!("export.json")}</option>
+                    <option value="csv" selected={*format == "csv"}>{t!("export.csv")}</option>
+                    <option value="markdown" selected={*format == "markdown"}>{t!("export.markdown")}</option>
+                    <option value="text" selected={*format == "text"}>{t!("export.text")}</option>
+                </select>
+            </div>
+
+            <div class="flex flex-row my-2 justify-between">
+                <label class="text-xl py-1">
+                    {t!("export.path")}
+                </label>
+                <input
+                    type="text"
+                    class="border border-gray-200 rounded-md px-2 py-1 ml-5 flex-1 dark:text-black"
+                    onchange={path_on_change}
+                    placeholder={t!("export.path_placeholder").to_string()}
+                    value={(*path).clone()}
+                />
+            </div>
+
+            // export button
+            <button
+                class="search-button bg-black my-2"
+                onclick={export_button_on_click}
+            >
+                <span
+                    class="dark:bg-white dark:text-black text-white"
+                > {t!("export.export_button")} </span>
+            </button>
+        </div>
     )
 }
@@ -0,0 +1,40 @@
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen_futures::spawn_local;
+/// the button used to import the user data exported by the export button
+use yew::{function_component, html, Callback, Html};
+
+use crate::invoke::invoke;
+
+#[derive(Serialize)]
+struct ImportDataArgs {
+    /// None means the default export file in the user download dir
+    path: Option<String>,
+    /// whether to apply the config saved in the export file
+    applyconfig: bool,
+}
+
+#[function_component(ImportButton)]
+pub fn import_button() -> Html {
+    let import_button_on_click = Callback::from(|_| {
+        spawn_local(async move {
+            let args = to_value(&ImportDataArgs {
+                path: None,
+                applyconfig: false,
+            })
+            .unwrap();
+            invoke("import_data_invoke", args).await;
+        });
+    });
+
+    html! (
+        <button
+            class="search-button bg-black my-2"
+            onclick={import_button_on_click}
+        >
+            <span
+                class="dark:bg-white dark:text-black text-white"
+            > {t!("export.import_button")} </span>
+        </button>
+    )
+}
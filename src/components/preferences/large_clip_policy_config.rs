@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+
+use yew::{function_component, html, Html};
+
+use crate::components::preferences::option_choose_config_template::OptionChooseConfigTemplate;
+
+#[function_component(LargeClipPolicyConfig)]
+pub fn large_clip_policy_config() -> Html {
+    html! {
+        <OptionChooseConfigTemplate
+            label={t!("preferences.large_clip_policy").to_string()}
+            get_value_invoke="get_large_clip_policy"
+            set_value_invoke="set_large_clip_policy"
+            default_value="truncate"
+            option={
+                let mut map = HashMap::new();
+                map.insert("skip".to_string(), t!("large_clip_policy.skip").to_string());
+                map.insert("truncate".to_string(), t!("large_clip_policy.truncate").to_string());
+                map
+            }
+        />
+    }
+}
@@ -0,0 +1,15 @@
+use yew::{function_component, html, Html};
+
+use super::int_config_template::IntConfigTemplate;
+
+#[function_component(MaxClipAgeDaysConfig)]
+pub fn max_clip_age_days_config() -> Html {
+    html! {
+        <IntConfigTemplate
+            label={"preferences.max_clip_age_days"}
+            default_value=0
+            set_value_invoke={"set_max_clip_age_days"}
+            get_value_invoke={"get_max_clip_age_days"}
+        />
+    }
+}
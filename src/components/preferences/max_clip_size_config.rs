@@ -0,0 +1,15 @@
+use yew::{function_component, html, Html};
+
+use super::int_config_template::IntConfigTemplate;
+
+#[function_component(MaxClipSizeConfig)]
+pub fn max_clip_size_config() -> Html {
+    html! {
+        <IntConfigTemplate
+            label={"preferences.max_clip_size"}
+            default_value=0
+            set_value_invoke={"set_max_clip_size"}
+            get_value_invoke={"get_max_clip_size"}
+        />
+    }
+}
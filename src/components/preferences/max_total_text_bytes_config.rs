@@ -0,0 +1,15 @@
+use yew::{function_component, html, Html};
+
+use super::int_config_template::IntConfigTemplate;
+
+#[function_component(MaxTotalTextBytesConfig)]
+pub fn max_total_text_bytes_config() -> Html {
+    html! {
+        <IntConfigTemplate
+            label={"preferences.max_total_text_bytes"}
+            default_value=0
+            set_value_invoke={"set_max_total_text_bytes"}
+            get_value_invoke={"get_max_total_text_bytes"}
+        />
+    }
+}
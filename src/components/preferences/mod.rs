@@ -1,12 +1,24 @@
+pub mod backup_keep_daily_config;
+pub mod backup_keep_weekly_config;
+pub mod backup_list;
 pub mod clips_per_page_config;
 pub mod clips_search_per_batch;
+pub mod compress_threshold_config;
 pub mod dark_mode_switch;
 pub mod export_button;
+pub mod import_button;
 pub mod language_config;
+pub mod large_clip_policy_config;
 pub mod log_level_filter_config;
+pub mod max_clip_age_days_config;
+pub mod max_clip_count_config;
 pub mod max_clip_len_config;
+pub mod max_clip_size_config;
+pub mod max_total_text_bytes_config;
 pub mod set_auto_delete_duplications;
 pub mod switch_pinned_status;
+pub mod trash_auto_empty_days_config;
+pub mod trash_list;
 
 mod int_config_template;
 mod option_choose_config_template;
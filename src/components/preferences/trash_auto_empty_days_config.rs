@@ -0,0 +1,15 @@
+use yew::{function_component, html, Html};
+
+use super::int_config_template::IntConfigTemplate;
+
+#[function_component(TrashAutoEmptyDaysConfig)]
+pub fn trash_auto_empty_days_config() -> Html {
+    html! {
+        <IntConfigTemplate
+            label={"preferences.trash_auto_empty_days"}
+            default_value=30
+            set_value_invoke={"set_trash_auto_empty_days"}
+            get_value_invoke={"get_trash_auto_empty_days"}
+        />
+    }
+}
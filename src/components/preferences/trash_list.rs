@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Event, HtmlInputElement};
+/// the list of the clips in the trash, and the buttons used to restore one of them or empty the trash
+use yew::{
+    function_component, html, use_effect_with, use_state, Callback, Html, TargetCast,
+    UseStateHandle,
+};
+
+use crate::invoke::invoke;
+
+/// the clip returned by get_trash_clips
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct TrashClip {
+    id: u64,
+    text: String,
+    /// unix time epoch in seconds
+    deleted_at: i64,
+}
+
+#[derive(Serialize)]
+struct RestoreClipArgs {
+    id: u64,
+}
+
+/// get the clips in the trash from the backend, the latest deleted first
+async fn get_trash_clips() -> Vec<TrashClip> {
+    let args = to_value(&()).unwrap();
+    let res = invoke("get_trash_clips", args).await;
+    serde_wasm_bindgen::from_value::<Vec<TrashClip>>(res).unwrap_or_default()
+}
+
+/// the text shown in the drop list
+fn trash_clip_display(clip: &TrashClip) -> String {
+    let time = match chrono::DateTime::from_timestamp(clip.deleted_at, 0) {
+        Some(time) => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => clip.deleted_at.to_string(),
+    };
+    let text: String = clip.text.chars().take(50).collect();
+    format!("{} - {}", time, text)
+}
+
+#[function_component(TrashList)]
+pub fn trash_list() -> Html {
+    let clips: UseStateHandle<Vec<TrashClip>> = use_state(Vec::new);
+    let selected: UseStateHandle<Option<u64>> = use_state(|| None);
+
+    let clips_1 = clips.clone();
+    let selected_1 = selected.clone();
+    use_effect_with((), move |_| {
+        spawn_local(async move {
+            let res = get_trash_clips().await;
+            selected_1.set(res.first().map(|clip| clip.id));
+            clips_1.set(res);
+        });
+    });
+
+    let selected_1 = selected.clone();
+    let clip_on_change = Callback::from(move |event: Event| {
+        let value = event.target_unchecked_into::<HtmlInputElement>().value();
+        selected_1.set(value.parse::<u64>().ok());
+    });
+
+    let clips_1 = clips.clone();
+    let selected_1 = selected.clone();
+    let restore_button_on_click = Callback::from(move |_| {
+        let id = match *selected_1 {
+            Some(id) => id,
+            None => return,
+        };
+        let clips_1 = clips_1.clone();
+        let selected_1 = selected_1.clone();
+        spawn_local(async move {
+            let args = to_value(&RestoreClipArgs { id }).unwrap();
+            invoke("restore_clip_from_trash", args).await;
+            let res = get_trash_clips().await;
+            selected_1.set(res.first().map(|clip| clip.id));
+            clips_1.set(res);
+        });
+    });
+
+    let clips_1 = clips.clone();
+    let selected_1 = selected.clone();
+    let empty_button_on_click = Callback::from(move |_| {
+        let clips_1 = clips_1.clone();
+        let selected_1 = selected_1.clone();
+        spawn_local(async move {
+            let args = to_value(&()).unwrap();
+            invoke("empty_trash_invoke", args).await;
+            selected_1.set(None);
+            clips_1.set(Vec::new());
+        });
+    });
+
+    html! (
+        <div class="flex flex-col">
+            <div class="flex flex-row my-2 justify-between">
+                <label class="text-xl">
+                    {t!("trash.trash")}
+                </label>
+                // trashed clips drop list
+                <select
+                    class="border border-gray-200 rounded-md p-2 text-lg dark:text-black"
+                    onchange={clip_on_change}
+                >
+                    {
+                        clips.iter().map(|clip| {
+                            html! {
+                                <option
+                                    value={clip.id.to_string()}
+                                    selected={*selected == Some(clip.id)}
+                                >
+                                    {trash_clip_display(clip)}
+                                </option>
+                            }
+                        }).collect::<Html>()
+                    }
+                </select>
+            </div>
+
+            // restore button
+            <button
+                class="search-button bg-black my-2"
+                onclick={restore_button_on_click}
+            >
+                <span
+                    class="dark:bg-white dark:text-black text-white"
+                > {t!("trash.restore_button")} </span>
+            </button>
+
+            // empty trash button
+            <button
+                class="search-button bg-black my-2"
+                onclick={empty_button_on_click}
+            >
+                <span
+                    class="dark:bg-white dark:text-black text-white"
+                > {t!("trash.empty_button")} </span>
+            </button>
+        </div>
+    )
+}
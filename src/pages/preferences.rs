@@ -3,11 +3,19 @@ use yew::{function_component, html, Html};
 use crate::components::{
     head_bar::HeadBar,
     preferences::{
+        backup_keep_daily_config::BackupKeepDailyConfig,
+        backup_keep_weekly_config::BackupKeepWeeklyConfig, backup_list::BackupList,
         clips_per_page_config::ClipsPerPageConfig,
-        clips_search_per_batch::SearchClipPerBatchConfig, dark_mode_switch::DarkModeSwitch,
-        export_button::ExportButton, language_config::LanguagesConfig,
-        log_level_filter_config::LogLevelFilterConfig, max_clip_len_config::MaxClipLenConfig,
+        clips_search_per_batch::SearchClipPerBatchConfig,
+        compress_threshold_config::CompressThresholdConfig, dark_mode_switch::DarkModeSwitch,
+        export_button::ExportButton, import_button::ImportButton, language_config::LanguagesConfig,
+        large_clip_policy_config::LargeClipPolicyConfig,
+        log_level_filter_config::LogLevelFilterConfig,
+        max_clip_age_days_config::MaxClipAgeDaysConfig, max_clip_count_config::MaxClipCountConfig,
+        max_clip_len_config::MaxClipLenConfig, max_clip_size_config::MaxClipSizeConfig,
+        max_total_text_bytes_config::MaxTotalTextBytesConfig,
         set_auto_delete_duplications::AutoDeleteDuplications,
+        trash_auto_empty_days_config::TrashAutoEmptyDaysConfig, trash_list::TrashList,
     },
 };
 
@@ -26,9 +34,31 @@ pub fn preferences() -> Html {
                 <br />
                 <AutoDeleteDuplications></AutoDeleteDuplications>
                 <br />
+                <MaxClipCountConfig></MaxClipCountConfig>
+                <br />
+                <MaxClipAgeDaysConfig></MaxClipAgeDaysConfig>
+                <br />
+                <MaxTotalTextBytesConfig></MaxTotalTextBytesConfig>
+                <br />
+                <MaxClipSizeConfig></MaxClipSizeConfig>
+                <br />
+                <LargeClipPolicyConfig></LargeClipPolicyConfig>
+                <br />
                 <LanguagesConfig></LanguagesConfig>
                 <br />
                 <ExportButton></ExportButton>
+                <br />
+                <ImportButton></ImportButton>
+                <br />
+                <BackupKeepDailyConfig></BackupKeepDailyConfig>
+                <br />
+                <BackupKeepWeeklyConfig></BackupKeepWeeklyConfig>
+                <br />
+                <BackupList></BackupList>
+                <br />
+                <TrashAutoEmptyDaysConfig></TrashAutoEmptyDaysConfig>
+                <br />
+                <TrashList></TrashList>
             </div>
 
             <h2 class="text-center text-4xl m-0">{ t!("preferences.advanced_title") }</h2>
@@ -36,6 +66,8 @@ pub fn preferences() -> Html {
                 <LogLevelFilterConfig></LogLevelFilterConfig>
                 <br />
                 <SearchClipPerBatchConfig></SearchClipPerBatchConfig>
+                <br />
+                <CompressThresholdConfig></CompressThresholdConfig>
             </div>
         </div>
     }
@@ -13,17 +13,23 @@ pub struct ClipWithSearchInfo {
     pub clip: Clip,
     pub score: i64,
     pub len: u64,
+    /// the position of the clip in the search result returned by the backend
+    pub rank: u64,
 }
 
 impl ClipWithSearchInfo {
     /// create a new clip from the search data and the clip data
-    pub fn from_clip(search_data: String, clip_res: Clip) -> Self {
+    pub fn from_clip(search_data: String, clip_res: Clip, rank: u64) -> Self {
+        // the text is only the preview of large clips, the size is the whole text
+        let len = clip_res.size.max(clip_res.text.len() as u64);
+
         // if the text is too long, we skip the fuzzy check.
         if clip_res.text.len() > MAX_LEN {
             return Self {
-                len: clip_res.text.len() as u64,
+                len,
                 clip: clip_res,
                 score: 0,
+                rank,
             };
         }
 
@@ -35,9 +41,10 @@ impl ClipWithSearchInfo {
         };
 
         Self {
-            len: clip_res.text.len() as u64,
+            len,
             clip: clip_res,
             score,
+            rank,
         }
     }
 }
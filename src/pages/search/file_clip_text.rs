@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen_futures::spawn_local;
+use yew::{function_component, html, use_effect_with, use_state, Html, Properties};
+
+use crate::invoke::invoke;
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct FileClipTextProps {
+    pub id: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct GetClipFilesArgs {
+    pub id: u64,
+}
+
+/// the file returned by get_clip_files
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ClipFile {
+    path: String,
+    name: String,
+    exists: bool,
+}
+
+/// show the files of a file clip, the missing files are crossed out
+#[function_component(FileClipText)]
+pub fn file_clip_text(props: &FileClipTextProps) -> Html {
+    let files = use_state(Vec::<ClipFile>::new);
+
+    let files_1 = files.clone();
+    use_effect_with(props.id, move |id| {
+        let id = *id;
+        spawn_local(async move {
+            let args = to_value(&GetClipFilesArgs { id }).unwrap();
+            let res = invoke("get_clip_files", args).await;
+            files_1.set(serde_wasm_bindgen::from_value::<Vec<ClipFile>>(res).unwrap_or_default());
+        });
+    });
+
+    html! {
+        <td class="border border-gray-200">
+            {
+                files.iter().map(|file| {
+                    let class = if file.exists {
+                        "block"
+                    } else {
+                        "block line-through text-red-500"
+                    };
+                    html! {
+                        <span class={class} title={file.path.clone()}>
+                            {file.name.clone()}
+                        </span>
+                    }
+                }).collect::<Html>()
+            }
+        </td>
+    }
+}
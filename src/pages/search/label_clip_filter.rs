@@ -0,0 +1,114 @@
+use std::rc::Rc;
+
+use clip::{LabelFilter, LabelMatch};
+use serde_wasm_bindgen::to_value;
+use web_sys::{Event, HtmlInputElement};
+use yew::{
+    function_component, html, platform::spawn_local, use_effect_with, use_state, Callback, Html,
+    Properties, TargetCast,
+};
+use yewdux::dispatch::Dispatch;
+
+use crate::{invoke::invoke, pages::search::search_clip::start_search};
+
+use super::{clip::SearchRes, SearchFullArgs};
+
+#[derive(PartialEq, Properties, Clone)]
+pub struct LabelClipFilterProps {
+    pub search_args_dispatch: Dispatch<SearchFullArgs>,
+    pub search_args: Rc<SearchFullArgs>,
+    pub search_res_dispatch: Dispatch<SearchRes>,
+}
+
+/// set the label filter and search again with it
+fn apply_label_filter(props: &LabelClipFilterProps, label_filter: LabelFilter) {
+    let mut search_args = props.search_args.self_copy();
+    search_args.label_filter = label_filter.clone();
+    props.search_args_dispatch.reduce_mut(|state| {
+        state.label_filter = label_filter;
+    });
+    start_search(
+        props.search_args_dispatch.clone(),
+        props.search_res_dispatch.clone(),
+        search_args,
+    );
+}
+
+/// choose for each label if the clips should have it, should not have it, or either,
+/// and if the clips should have all or any of the included labels
+#[function_component(LabelClipFilter)]
+pub fn label_clip_filter(props: &LabelClipFilterProps) -> Html {
+    let labels = use_state(Vec::<String>::new);
+
+    let labels_1 = labels.clone();
+    use_effect_with((), move |_| {
+        spawn_local(async move {
+            let res = invoke("get_all_labels", to_value(&()).unwrap()).await;
+            labels_1.set(serde_wasm_bindgen::from_value::<Vec<String>>(res).unwrap_or_default());
+        });
+    });
+
+    let props_1 = props.clone();
+    let mode_on_change = Callback::from(move |event: Event| {
+        let value = event.target_unchecked_into::<HtmlInputElement>().value();
+        let mut label_filter = props_1.search_args.label_filter.clone();
+        label_filter.mode = if value == "any" {
+            LabelMatch::Any
+        } else {
+            LabelMatch::All
+        };
+        apply_label_filter(&props_1, label_filter);
+    });
+
+    let label_filter = &props.search_args.label_filter;
+
+    html! {
+        <div class="flex flex-row flex-wrap my-2 justify-between">
+            <label htmlFor="search-page-label-filter" class="text-xl py-1">
+                {t!("search.labels")}
+            </label>
+            <div class="flex flex-row flex-wrap">
+                {
+                    labels.iter().map(|label| {
+                        let props_1 = props.clone();
+                        let label_1 = label.clone();
+                        let on_change = Callback::from(move |event: Event| {
+                            let value = event.target_unchecked_into::<HtmlInputElement>().value();
+                            let mut label_filter = props_1.search_args.label_filter.clone();
+                            label_filter.include.retain(|l| *l != label_1);
+                            label_filter.exclude.retain(|l| *l != label_1);
+                            match value.as_str() {
+                                "include" => label_filter.include.push(label_1.clone()),
+                                "exclude" => label_filter.exclude.push(label_1.clone()),
+                                _ => {}
+                            }
+                            apply_label_filter(&props_1, label_filter);
+                        });
+                        let included = label_filter.include.contains(label);
+                        let excluded = label_filter.exclude.contains(label);
+                        html! {
+                            <div class="flex flex-row mx-2">
+                                <span class="text-lg py-2 mr-1">{label.clone()}</span>
+                                <select
+                                    class="border border-gray-200 rounded-md p-2 text-lg dark:text-black"
+                                    onchange={on_change}
+                                >
+                                    <option value="either" selected={!included && !excluded}>{t!("search.label_either")}</option>
+                                    <option value="include" selected={included}>{t!("search.label_include")}</option>
+                                    <option value="exclude" selected={excluded}>{t!("search.label_exclude")}</option>
+                                </select>
+                            </div>
+                        }
+                    }).collect::<Html>()
+                }
+                <select
+                    class="border border-gray-200 rounded-md p-2 mx-2 text-lg dark:text-black"
+                    onchange={mode_on_change}
+                >
+                    <option value="all" selected={label_filter.mode == LabelMatch::All}>{t!("search.label_all")}</option>
+                    <option value="any" selected={label_filter.mode == LabelMatch::Any}>{t!("search.label_any")}</option>
+                </select>
+            </div>
+        </div>
+    }
+}
@@ -1,21 +1,23 @@
 use std::sync::Arc;
 
+use clip::LabelFilter;
 use serde::Deserialize;
 use serde::Serialize;
-use yew::platform::spawn_local;
 use yew::use_effect_with;
 use yew::{function_component, html, Callback, Html, TargetCast};
 
 use web_sys::{Event, HtmlInputElement};
 
+use crate::pages::search::label_clip_filter::LabelClipFilter;
 use crate::pages::search::search_method::SearchMethod;
 use crate::pages::search::search_res_table::SearchResTable;
+use crate::pages::search::undo_button::UndoButton;
 use crate::{
     components::head_bar::HeadBar,
     pages::search::{
         clip::SearchRes,
         order::OrderOrder,
-        search_clip::search_clips,
+        search_clip::start_search,
         search_state::{SearchState, SearchStateHtml},
     },
 };
@@ -25,17 +27,18 @@ use self::order::OrderMethod;
 mod clip;
 mod copy_clip_button;
 mod favourite_button;
-mod favourite_clip_filter;
+mod file_clip_text;
 mod fuzzy_search_text;
+mod label_clip_filter;
 mod order;
 mod pin_clip_button;
-mod pin_clip_filter;
 mod search_clip;
 mod search_method;
 mod search_res_table;
 mod search_state;
 mod time_display;
 mod trash_clip_button;
+mod undo_button;
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct UserIdLimit {
@@ -71,8 +74,8 @@ pub struct SearchFullArgs {
     pub search_data: Arc<String>,
     pub order_by: OrderMethod,
     pub order_order: OrderOrder,
-    pub favourite_filter: bool,
-    pub pin_filter: bool,
+    #[serde(default)]
+    pub label_filter: LabelFilter,
     pub total_search_res_limit: usize,
     pub user_id_limit: UserIdLimit,
 }
@@ -91,8 +94,7 @@ impl Default for SearchFullArgs {
             search_data: Arc::new("".to_string()),
             order_by: OrderMethod::Time,
             order_order: OrderOrder::Desc,
-            favourite_filter: false,
-            pin_filter: false,
+            label_filter: LabelFilter::default(),
             total_search_res_limit: 100,
             user_id_limit: UserIdLimit::default(),
         }
@@ -122,50 +124,54 @@ pub fn search() -> Html {
             state.search_method = SearchMethod::from(value);
         });
 
-    let order_method_on_change =
-        search_args_dispatch.reduce_mut_callback_with(|state, event: Event| {
-            let value = event.target_unchecked_into::<HtmlInputElement>().value();
-            state.order_by = OrderMethod::from(value);
+    // the clips are ordered by the backend, so search again when the order changes
+    let search_res_dispatch_1 = search_res_dispatch.clone();
+    let search_args_dispatch_1 = search_args_dispatch.clone();
+    let search_args_1 = search_args.clone();
+    let order_method_on_change = Callback::from(move |event: Event| {
+        let value = event.target_unchecked_into::<HtmlInputElement>().value();
+        let mut search_args = search_args_1.self_copy();
+        search_args.order_by = OrderMethod::from(value);
+        search_args_dispatch_1.reduce_mut(|state| {
+            state.order_by = search_args.order_by.clone();
         });
+        start_search(
+            search_args_dispatch_1.clone(),
+            search_res_dispatch_1.clone(),
+            search_args,
+        );
+    });
 
-    let order_order_on_change =
-        search_args_dispatch.reduce_mut_callback_with(|state, event: Event| {
-            let value = event.target_unchecked_into::<HtmlInputElement>().value();
-            if value == "desc" {
-                state.order_order = OrderOrder::Desc;
-            } else {
-                state.order_order = OrderOrder::Asc;
-            }
+    let search_res_dispatch_1 = search_res_dispatch.clone();
+    let search_args_dispatch_1 = search_args_dispatch.clone();
+    let search_args_1 = search_args.clone();
+    let order_order_on_change = Callback::from(move |event: Event| {
+        let value = event.target_unchecked_into::<HtmlInputElement>().value();
+        let mut search_args = search_args_1.self_copy();
+        search_args.order_order = if value == "desc" {
+            OrderOrder::Desc
+        } else {
+            OrderOrder::Asc
+        };
+        search_args_dispatch_1.reduce_mut(|state| {
+            state.order_order = search_args.order_order.clone();
         });
+        start_search(
+            search_args_dispatch_1.clone(),
+            search_res_dispatch_1.clone(),
+            search_args,
+        );
+    });
 
     let search_res_dispatch_1 = search_res_dispatch.clone();
     let search_args_dispatch_1 = search_args_dispatch.clone();
     let search_args_1 = search_args.clone();
     let search_button_on_click = Callback::from(move |_| {
-        let search_args_dispatch = search_args_dispatch_1.clone();
-        let search_args = search_args_1.clone();
-        let search_res_dispatch = search_res_dispatch_1.clone();
-        search_args_dispatch.reduce_mut(|state| {
-            state.search_state = SearchState::Searching;
-        });
-        spawn_local(async move {
-            let res = search_clips(
-                search_res_dispatch,
-                search_args.self_copy(),
-                search_args.favourite_filter,
-                search_args.pin_filter,
-            );
-            let res = res.await;
-            if let Err(err) = res {
-                search_args_dispatch.reduce_mut(|state| {
-                    state.search_state = SearchState::Error(err);
-                });
-            } else {
-                search_args_dispatch.reduce_mut(|state| {
-                    state.search_state = SearchState::Finished;
-                });
-            }
-        });
+        start_search(
+            search_args_dispatch_1.clone(),
+            search_res_dispatch_1.clone(),
+            search_args_1.self_copy(),
+        );
     });
 
     let total_search_res_limit_on_change =
@@ -204,17 +210,9 @@ pub fn search() -> Html {
         });
 
     let search_args_dispatch_1 = search_args_dispatch.clone();
-    let search_args_dispatch_2 = search_args_dispatch.clone();
     use_effect_with((), move |_| {
         search_args_dispatch.reduce_mut(|state| {
-            state.favourite_filter = false;
-        });
-    });
-
-    let search_args_dispatch_3 = search_args_dispatch_2.clone();
-    use_effect_with((), move |_| {
-        search_args_dispatch_2.reduce_mut(|state| {
-            state.pin_filter = false;
+            state.label_filter = LabelFilter::default();
         });
     });
 
@@ -250,6 +248,7 @@ pub fn search() -> Html {
                             <option value="fuzzy" selected={SearchMethod::Fuzzy == search_args.search_method}>{t!("search.fuzzy")}</option>
                             <option value="normal" selected={SearchMethod::Normal == search_args.search_method}>{t!("search.normal")}</option>
                             <option value="regexp" selected={SearchMethod::Regexp == search_args.search_method}>{t!("search.regex")}</option>
+                            <option value="fulltext" selected={SearchMethod::Fulltext == search_args.search_method}>{t!("search.fulltext")}</option>
                         </select>
                     </div>
 
@@ -269,6 +268,7 @@ pub fn search() -> Html {
                                 <option value="fuzzy_score" selected={OrderMethod::FuzzyScore == search_args.order_by}>{t!("search.score")}</option>
                                 <option value="text" selected={OrderMethod::Text == search_args.order_by}>{t!("search.text")}</option>
                                 <option value="size" selected={OrderMethod::Size == search_args.order_by}>{t!("search.length")}</option>
+                                <option value="relevance" selected={OrderMethod::Relevance == search_args.order_by}>{t!("search.relevance")}</option>
                             </select>
                             // order order drop list
                             <select
@@ -320,6 +320,13 @@ pub fn search() -> Html {
                         />
                     </div>
 
+                    // label filter
+                    <LabelClipFilter
+                        search_args_dispatch={search_args_dispatch_1}
+                        search_args={search_args.clone()}
+                        search_res_dispatch={search_res_dispatch.clone()}
+                    ></LabelClipFilter>
+
                     // search button
                     <button
                         class="search-button bg-black my-2"
@@ -330,6 +337,9 @@ pub fn search() -> Html {
                         > {t!("search.press_to_search")} </span>
                     </button>
 
+                    // undo the last delete or label change
+                    <UndoButton></UndoButton>
+
                     // search state
                     <SearchStateHtml state={search_args.clone()}></SearchStateHtml>
 
@@ -338,8 +348,6 @@ pub fn search() -> Html {
                         search_args={search_args}
                         search_res={search_res}
                         search_res_dispatch={search_res_dispatch}
-                        favourite_filter_dispatch={search_args_dispatch_1}
-                        pin_filter_dispatch={search_args_dispatch_3}
                     ></SearchResTable>
                 </div>
             </div>
@@ -1,9 +1,7 @@
-use std::{fmt::Display, rc::Rc, sync::Mutex};
+use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-use super::clip::ClipWithSearchInfo;
-
 #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub enum OrderOrder {
     Desc,
@@ -19,11 +17,15 @@ impl Display for OrderOrder {
     }
 }
 
-/// the order method module
+/// the order method module,
+/// the clips are ordered by the backend across the whole history
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum OrderMethod {
-    /// order by fuzzy score
+    /// order by the score of the search method,
+    /// the fuzzy score for the fuzzy search, the relevance for the full text search
     FuzzyScore,
+    /// the same as the fuzzy score
+    Relevance,
     /// order by size
     Size,
     /// order by text
@@ -36,6 +38,7 @@ impl Display for OrderMethod {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             OrderMethod::FuzzyScore => write!(f, "fuzzy_score"),
+            OrderMethod::Relevance => write!(f, "relevance"),
             OrderMethod::Size => write!(f, "size"),
             OrderMethod::Text => write!(f, "text"),
             OrderMethod::Time => write!(f, "time"),
@@ -47,6 +50,7 @@ impl From<&str> for OrderMethod {
     fn from(s: &str) -> Self {
         match s {
             "fuzzy_score" => OrderMethod::FuzzyScore,
+            "relevance" => OrderMethod::Relevance,
             "size" => OrderMethod::Size,
             "text" => OrderMethod::Text,
             "time" => OrderMethod::Time,
@@ -60,50 +64,3 @@ impl From<String> for OrderMethod {
         OrderMethod::from(s.as_str())
     }
 }
-
-pub fn sort_search_res(
-    res: Rc<Mutex<Vec<ClipWithSearchInfo>>>,
-    method: OrderMethod,
-    order: OrderOrder,
-) {
-    match order {
-        OrderOrder::Asc => sort_search_res_asc(res, method),
-        OrderOrder::Desc => sort_search_res_desc(res, method),
-    }
-}
-
-fn sort_search_res_asc(res: Rc<Mutex<Vec<ClipWithSearchInfo>>>, method: OrderMethod) {
-    let mut res = res.lock().unwrap();
-    match method {
-        OrderMethod::Time => {
-            res.sort_by(|a, b| a.clip.timestamp.cmp(&b.clip.timestamp));
-        }
-        OrderMethod::FuzzyScore => {
-            res.sort_by(|a, b| a.score.cmp(&b.score));
-        }
-        OrderMethod::Text => {
-            res.sort_by(|a, b| a.clip.text.cmp(&b.clip.text));
-        }
-        OrderMethod::Size => {
-            res.sort_by(|a, b| a.len.cmp(&b.len));
-        }
-    }
-}
-
-fn sort_search_res_desc(res: Rc<Mutex<Vec<ClipWithSearchInfo>>>, method: OrderMethod) {
-    let mut res = res.lock().unwrap();
-    match method {
-        OrderMethod::Time => {
-            res.sort_by(|a, b| b.clip.timestamp.cmp(&a.clip.timestamp));
-        }
-        OrderMethod::FuzzyScore => {
-            res.sort_by(|a, b| b.score.cmp(&a.score));
-        }
-        OrderMethod::Text => {
-            res.sort_by(|a, b| b.clip.text.cmp(&a.clip.text));
-        }
-        OrderMethod::Size => {
-            res.sort_by(|a, b| b.len.cmp(&a.len));
-        }
-    }
-}
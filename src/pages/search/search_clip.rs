@@ -1,111 +1,127 @@
-use std::collections::HashMap;
-
-use clip::Clip;
+use clip::{LabelFilter, SearchPage};
 use serde::Serialize;
 use serde_wasm_bindgen::to_value;
+use yew::platform::spawn_local;
+use yewdux::dispatch::Dispatch;
 
 /// TODO because of I cannot find a component for the date-picker, so I do not implement the date-picker
 use crate::invoke::invoke;
 
 use super::{
     clip::{ClipWithSearchInfo, SearchRes},
+    search_state::SearchState,
     SearchFullArgs,
 };
 
 /// search args
 #[derive(Serialize)]
 struct SearchArgs {
-    pub data: String,
+    /// -1 means no limit
     pub minid: i64,
     /// -1 means no limit
     pub maxid: i64,
-    /// fuzzy, fast, normal
+    pub data: String,
+    /// fuzzy, normal, regexp, fulltext
     pub searchmethod: String,
-    /// favourite filter
-    pub favourite: bool,
-    /// pinned filter
-    pub pinned: bool,
+    /// the clips should match the label filter
+    pub labels: LabelFilter,
+    /// time, fuzzy_score, relevance, text, size
+    pub order: String,
+    /// asc, desc
+    pub direction: String,
+    /// the cursor of the previous page, None for the first page
+    pub cursor: Option<String>,
 }
 
 /// search for a clip in the database
 ///
 /// the method is decide by the input, and whenever the method is changed, the search will be reset
 ///
-/// the search method is fuzzy, fast, normal
+/// the clips are ordered by the backend across the whole history,
+/// the pages are fetched with the cursor until the total search res limit is reached
 ///
 /// search state, if not finished, it will be None, if finished, it will be Some(Ok(())) or Some(Err(String))
 pub async fn search_clips(
-    search_res_dispatch: yewdux::dispatch::Dispatch<SearchRes>,
+    search_res_dispatch: Dispatch<SearchRes>,
     search_full_args: SearchFullArgs,
-    favourite_filter: bool,
-    pinned_filter: bool,
 ) -> Result<(), String> {
     search_res_dispatch.reduce_mut(|state| {
         state.rebuild_num += 1;
         state.res = std::rc::Rc::new(std::sync::Mutex::new(Vec::new()));
     });
 
-    let args = to_value(&()).unwrap();
-    let mut max_id = search_full_args.user_id_limit.max;
-    if max_id < 0 {
-        let res = invoke("get_max_id", args).await;
-        max_id = res.as_f64().unwrap() as i64 + 1;
-    }
+    let mut cursor = None;
     let mut total_len = 0;
 
-    while max_id > search_full_args.user_id_limit.min
-        && total_len < search_full_args.total_search_res_limit
-    {
-        // the min_id is 0
-        // data is the value
-        // search_method is the search_method
+    while total_len < search_full_args.total_search_res_limit {
         let args = to_value(&SearchArgs {
+            minid: search_full_args.user_id_limit.min,
+            maxid: search_full_args.user_id_limit.max,
             data: search_full_args.search_data.clone().to_string(),
-            minid: -1,
-            maxid: max_id,
             searchmethod: search_full_args.search_method.clone().to_string(),
-            favourite: favourite_filter,
-            pinned: pinned_filter,
+            labels: search_full_args.label_filter.clone(),
+            order: search_full_args.order_by.to_string(),
+            direction: search_full_args.order_order.to_string(),
+            cursor: cursor.clone(),
         })
         .unwrap();
 
         let res = invoke("search_clips", args).await;
-        let res = serde_wasm_bindgen::from_value::<HashMap<String, Clip>>(res);
-        if let Ok(res) = res {
-            if res.is_empty() {
-                break;
-            }
-            for (id, clip) in res {
-                let id = id.parse::<u64>().unwrap();
-                max_id -= 1;
-                if (id as i64) < max_id {
-                    max_id = id as i64;
-                }
+        let res = serde_wasm_bindgen::from_value::<SearchPage>(res);
+        let page = match res {
+            Ok(page) => page,
+            Err(err) => return Err(err.to_string()),
+        };
 
-                // if the clip is not the duplication of the last clip, then push it
-                search_res_dispatch.reduce_mut(|state| {
-                    let mut state = state.res.lock().unwrap();
-                    let last_clip_id = state.iter().find(|clip| clip.clip.id == id);
-                    if last_clip_id.is_some() {
-                        return;
-                    }
-                    state.push(ClipWithSearchInfo::from_clip(
-                        search_full_args.search_data.to_string(),
-                        clip,
-                    ));
-                    total_len += 1;
-                });
+        for clip in page.clips {
+            if total_len >= search_full_args.total_search_res_limit {
+                break;
             }
 
             search_res_dispatch.reduce_mut(|state| {
-                state.rebuild_num += 1;
+                let mut state = state.res.lock().unwrap();
+                let rank = state.len() as u64;
+                state.push(ClipWithSearchInfo::from_clip(
+                    search_full_args.search_data.to_string(),
+                    clip,
+                    rank,
+                ));
+                total_len += 1;
             });
-        } else {
-            let res = res.err().unwrap();
-            let err = res.to_string();
-            return Err(err);
+        }
+
+        search_res_dispatch.reduce_mut(|state| {
+            state.rebuild_num += 1;
+        });
+
+        match page.cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
         }
     }
 
     Ok(())
 }
+
+/// set the search state to searching, search with the args, and set the result state
+pub fn start_search(
+    search_args_dispatch: Dispatch<SearchFullArgs>,
+    search_res_dispatch: Dispatch<SearchRes>,
+    search_args: SearchFullArgs,
+) {
+    search_args_dispatch.reduce_mut(|state| {
+        state.search_state = SearchState::Searching;
+    });
+    spawn_local(async move {
+        let res = search_clips(search_res_dispatch, search_args).await;
+        if let Err(err) = res {
+            search_args_dispatch.reduce_mut(|state| {
+                state.search_state = SearchState::Error(err);
+            });
+        } else {
+            search_args_dispatch.reduce_mut(|state| {
+                state.search_state = SearchState::Finished;
+            });
+        }
+    });
+}
@@ -12,6 +12,8 @@ pub enum SearchMethod {
     Regexp,
     /// search using fuzzy search
     Fuzzy,
+    /// search using the full text index, the result is ranked by relevance
+    Fulltext,
 }
 
 impl Display for SearchMethod {
@@ -20,6 +22,7 @@ impl Display for SearchMethod {
             SearchMethod::Normal => f.write_str("normal"),
             SearchMethod::Regexp => f.write_str("regexp"),
             SearchMethod::Fuzzy => f.write_str("fuzzy"),
+            SearchMethod::Fulltext => f.write_str("fulltext"),
         }
     }
 }
@@ -30,6 +33,7 @@ impl From<&str> for SearchMethod {
             "normal" => SearchMethod::Normal,
             "regexp" => SearchMethod::Regexp,
             "fuzzy" => SearchMethod::Fuzzy,
+            "fulltext" => SearchMethod::Fulltext,
             _ => SearchMethod::Normal,
         }
     }
@@ -1,12 +1,12 @@
 use std::rc::Rc;
 
+use clip::ClipType;
 use yew::{function_component, html, Html, Properties};
 use yew_icons::{Icon, IconId};
 
 use crate::pages::search::{
     copy_clip_button::CopyClipButton, favourite_button::FavouriteClipButton,
-    favourite_clip_filter::FavouriteClipFilter, fuzzy_search_text::SearchText,
-    order::sort_search_res, pin_clip_button::PinClipButton, pin_clip_filter::PinClipFilter,
+    file_clip_text::FileClipText, fuzzy_search_text::SearchText, pin_clip_button::PinClipButton,
     time_display::TimeDisplay, trash_clip_button::TrashClipButton,
 };
 
@@ -18,17 +18,10 @@ pub struct SearchResTableProps {
     pub search_args: Rc<SearchFullArgs>,
     pub search_res: Rc<SearchRes>,
     pub search_res_dispatch: yewdux::prelude::Dispatch<SearchRes>,
-    pub favourite_filter_dispatch: yewdux::prelude::Dispatch<SearchFullArgs>,
-    pub pin_filter_dispatch: yewdux::prelude::Dispatch<SearchFullArgs>,
 }
 
 #[function_component(SearchResTable)]
 pub fn search_res_table_html(props: &SearchResTableProps) -> Html {
-    sort_search_res(
-        props.search_res.res.clone(),
-        props.search_args.order_by.clone(),
-        props.search_args.order_order.clone(),
-    );
     let res = props.search_res.res.lock().unwrap();
 
     html! {
@@ -46,22 +39,13 @@ pub fn search_res_table_html(props: &SearchResTableProps) -> Html {
                         </th>
                         // favourite or not, use heart icon
                         <th class="border border-gray-200">
-                            /* <Icon icon_id={IconId::BootstrapHeartHalf} class="mx-auto mt-0.5"/> */
-                            <FavouriteClipFilter
-                            favourite_filter_dispatch={props.favourite_filter_dispatch.clone()}
-                            search_args={props.search_args.clone()}
-                            search_res_dispatch={props.search_res_dispatch.clone()}
-                            ></FavouriteClipFilter>
+                            <Icon icon_id={IconId::BootstrapHeartHalf} class="mx-auto mt-0.5"/>
                         </th>
                         // the fuzzy score of the clip
                         <th class="border border-gray-200">{ "Score" }</th>
                         // pin the clip button icon
                         <th class="border border-gray-200">
-                            <PinClipFilter
-                            pin_filter_dispatch={props.pin_filter_dispatch.clone()}
-                            search_args={props.search_args.clone()}
-                            search_res_dispatch={props.search_res_dispatch.clone()}
-                            ></PinClipFilter>
+                            <Icon icon_id={IconId::BootstrapPinAngleFill} class="mx-auto mt-0.5"/>
                         </th>
                         // copy the clip button icon
                         <th class="border border-gray-200">
@@ -87,7 +71,11 @@ pub fn search_res_table_html(props: &SearchResTableProps) -> Html {
                                     <td class="border border-gray-200 text-center">{clip.score}</td>
                                     <PinClipButton id={clip.clip.id} pinned={clip.clip.labels.contains(&"pinned".to_string())}></PinClipButton>
                                     <CopyClipButton id={clip.clip.id}></CopyClipButton>
-                                    <SearchText text={clip.clip.text.clone()} data={props.search_args.search_data.clone()} search_method={props.search_args.search_method.clone()}></SearchText>
+                                    if clip.clip.clip_type == ClipType::File {
+                                        <FileClipText id={clip.clip.id}></FileClipText>
+                                    } else {
+                                        <SearchText text={clip.clip.text.clone()} data={props.search_args.search_data.clone()} search_method={props.search_args.search_method.clone()}></SearchText>
+                                    }
                                     <TrashClipButton id={clip.clip.id} search_res_dispatch={props.search_res_dispatch.clone()}></TrashClipButton>
                                 </tr>
                             }
@@ -0,0 +1,27 @@
+/// invoke undo_last_action
+use wasm_bindgen_futures::spawn_local;
+use yew::{function_component, html, Callback, Html};
+
+use crate::invoke::invoke;
+
+#[function_component(UndoButton)]
+pub fn undo_button() -> Html {
+    let undo_button_on_click = Callback::from(move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&()).unwrap();
+            // the backend sends a notification telling what is undone
+            invoke("undo_last_action", args).await;
+        });
+    });
+
+    html! {
+        <button
+            class="search-button bg-black my-2"
+            onclick={undo_button_on_click}
+        >
+            <span
+                class="dark:bg-white dark:text-black text-white"
+            > {t!("search.undo")} </span>
+        </button>
+    }
+}